@@ -0,0 +1,86 @@
+//! A reference signer serving a local keystore directory to the remote keystore of a node.
+//!
+//! The keys are inserted in the directory as for a node, with
+//! `node-template key insert --keystore-path <directory>`, or, when the signer is started with
+//! `--allow-new-keys`, through the `author_insertKey` RPC of the node using it.
+//!
+//! With `--token-file`, the requests must carry the token of the file as a bearer token, which
+//! the node reads from the `KEYSTORE_SIGNER_TOKEN` environment variable. The token is required
+//! to listen on a TCP address reachable from other hosts.
+
+use std::{error::Error as StdError, fs, net::ToSocketAddrs, path::PathBuf, sync::Arc, thread};
+
+use clap::Parser;
+use remote_keystore::{transport, Endpoint, Signer};
+use sc_keystore::LocalKeystore;
+use sp_core::crypto::SecretString;
+
+#[derive(Debug, Parser)]
+struct Cli {
+	/// The keystore directory.
+	#[clap(long)]
+	keystore_path: PathBuf,
+
+	/// Where to listen for the node requests, `http://<host>:<port>` or `unix://<socket path>`.
+	#[clap(long, default_value = "http://127.0.0.1:9955")]
+	listen: Endpoint,
+
+	/// A file holding the password of the keystore.
+	#[clap(long)]
+	password_filename: Option<PathBuf>,
+
+	/// A file holding the token the requests must carry. Required to listen on a TCP address
+	/// reachable from other hosts.
+	#[clap(long)]
+	token_file: Option<PathBuf>,
+
+	/// Generate and insert the new keys requested by the node, like with `author_rotateKeys` or
+	/// `author_insertKey`. Otherwise only the keys already in the keystore are served.
+	#[clap(long)]
+	allow_new_keys: bool,
+}
+
+/// Reads a secret from the first line of `path`.
+fn read_secret(path: PathBuf) -> std::io::Result<String> {
+	fs::read_to_string(path).map(|secret| secret.trim_end().to_owned())
+}
+
+fn main() -> Result<(), Box<dyn StdError>> {
+	let cli = Cli::parse();
+	let token = cli.token_file.map(read_secret).transpose()?;
+	if token.as_deref().map_or(false, str::is_empty) {
+		return Err("the token file is empty".into())
+	}
+	if let Endpoint::Http { address, .. } = &cli.listen {
+		let external = address.to_socket_addrs()?.any(|address| !address.ip().is_loopback());
+		if external && token.is_none() {
+			return Err(format!(
+				"refusing to listen on {}, reachable from other hosts, without --token-file",
+				address
+			)
+			.into())
+		}
+	}
+
+	let password = cli.password_filename.map(read_secret).transpose()?.map(SecretString::new);
+	let keystore = LocalKeystore::open(&cli.keystore_path, password)?;
+	let signer = Arc::new(Signer::new(keystore, cli.allow_new_keys));
+	let listener = cli.listen.listen()?;
+	eprintln!("Serving the keystore {} on {}", cli.keystore_path.display(), cli.listen);
+
+	loop {
+		match listener.accept() {
+			Ok(connection) => {
+				let (signer, token) = (signer.clone(), token.clone());
+				thread::spawn(move || {
+					let result =
+						transport::serve(connection, token.as_deref(), |body| signer.respond(body));
+					if let Err(e) = result {
+						eprintln!("Serving a request failed: {}", e);
+					}
+				});
+			},
+			Err(e) => eprintln!("Accepting a connection failed: {}", e),
+		}
+	}
+}
@@ -0,0 +1,410 @@
+//! The keystore forwarding every operation to the signer.
+
+use std::panic;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use sp_core::{
+	crypto::{ByteArray, CryptoTypeId, CryptoTypePublicPair, KeyTypeId},
+	ecdsa, ed25519, sr25519, Bytes,
+};
+use sp_keystore::{
+	vrf::{VRFSignature, VRFTranscriptData},
+	CryptoStore, Error, SyncCryptoStore,
+};
+
+use crate::{
+	protocol::{Id, Request, Response},
+	transport::Endpoint,
+};
+
+const LOG_TARGET: &str = "keystore::remote";
+
+/// The environment variable holding the token sent to the signer.
+pub const TOKEN_ENV: &str = "KEYSTORE_SIGNER_TOKEN";
+
+/// A keystore whose keys are held by a remote signer.
+///
+/// The requests to the signer are blocking: the [`SyncCryptoStore`] implementation sends them
+/// from the calling thread, and the [`CryptoStore`] one from the blocking threads of the tokio
+/// runtime, so that a slow signer does not stall the async tasks of the node.
+#[derive(Clone)]
+pub struct RemoteKeystore {
+	endpoint: Endpoint,
+	token: Option<String>,
+}
+
+impl RemoteKeystore {
+	/// Opens the keystore of the signer at `uri`, `http://<host>:<port>` or
+	/// `unix://<socket path>`, sending it the bearer `token` if any, and checks that the signer
+	/// answers.
+	pub fn open(uri: &str, token: Option<String>) -> Result<Self, Error> {
+		let keystore = Self { endpoint: uri.parse().map_err(Error::Other)?, token };
+		keystore.request::<bool>(&Request::HasKeys { keys: Vec::new() })?;
+		Ok(keystore)
+	}
+
+	/// Runs `f` on the blocking threads of the tokio runtime.
+	async fn blocking<R: Send + 'static>(&self, f: impl FnOnce(&Self) -> R + Send + 'static) -> R {
+		let keystore = self.clone();
+		tokio::task::spawn_blocking(move || f(&keystore))
+			.await
+			.unwrap_or_else(|e| panic::resume_unwind(e.into_panic()))
+	}
+
+	fn request<T: DeserializeOwned>(&self, request: &Request) -> Result<T, Error> {
+		let body = serde_json::to_vec(request).map_err(|e| Error::Other(e.to_string()))?;
+		let reply = self.endpoint.request(&body, self.token.as_deref()).map_err(|e| {
+			log::warn!(target: LOG_TARGET, "Request to the signer {} failed: {}", self.endpoint, e);
+			Error::Unavailable
+		})?;
+		serde_json::from_slice::<Response<T>>(&reply)
+			.map_err(|e| Error::Other(format!("invalid signer response: {}", e)))?
+			.map_err(Into::into)
+	}
+
+	fn fetch_keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let keys: Vec<(Id, Bytes)> = self.request(&Request::Keys { key_type: id.0 })?;
+		Ok(keys
+			.into_iter()
+			.map(|(crypto_type, public)| CryptoTypePublicPair(CryptoTypeId(crypto_type), public.0))
+			.collect())
+	}
+
+	fn public_keys<P: ByteArray>(&self, id: KeyTypeId, crypto_type: CryptoTypeId) -> Vec<P> {
+		match self.fetch_keys(id) {
+			Ok(keys) => keys
+				.into_iter()
+				.filter(|key| key.0 == crypto_type)
+				.filter_map(|key| P::from_slice(&key.1).ok())
+				.collect(),
+			Err(e) => {
+				log::warn!(target: LOG_TARGET, "Fetching the {:?} keys failed: {}", id, e);
+				Vec::new()
+			},
+		}
+	}
+
+	fn generate<P: ByteArray>(
+		&self,
+		id: KeyTypeId,
+		crypto_type: CryptoTypeId,
+		seed: Option<&str>,
+	) -> Result<P, Error> {
+		let public: Bytes = self.request(&Request::Generate {
+			key_type: id.0,
+			crypto_type: crypto_type.0,
+			seed: seed.map(Into::into),
+		})?;
+		P::from_slice(&public)
+			.map_err(|()| Error::ValidationError("invalid public key from the signer".into()))
+	}
+}
+
+impl SyncCryptoStore for RemoteKeystore {
+	fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.public_keys(id, sr25519::CRYPTO_ID)
+	}
+
+	fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		self.generate(id, sr25519::CRYPTO_ID, seed)
+	}
+
+	fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.public_keys(id, ed25519::CRYPTO_ID)
+	}
+
+	fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		self.generate(id, ed25519::CRYPTO_ID, seed)
+	}
+
+	fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.public_keys(id, ecdsa::CRYPTO_ID)
+	}
+
+	fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		self.generate(id, ecdsa::CRYPTO_ID, seed)
+	}
+
+	fn insert_unknown(&self, key_type: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		self.request(&Request::InsertUnknown {
+			key_type: key_type.0,
+			suri: suri.into(),
+			public: Bytes(public.to_vec()),
+		})
+		.map_err(|e| log::warn!(target: LOG_TARGET, "Inserting a {:?} key failed: {}", key_type, e))
+	}
+
+	fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		let available = self.fetch_keys(id)?;
+		Ok(keys.into_iter().filter(|key| available.contains(key)).collect())
+	}
+
+	fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.fetch_keys(id)
+	}
+
+	fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let keys = public_keys
+			.iter()
+			.map(|(public, key_type)| (Bytes(public.clone()), key_type.0))
+			.collect();
+		self.request(&Request::HasKeys { keys }).unwrap_or_else(|e| {
+			log::warn!(target: LOG_TARGET, "Checking the keys failed: {}", e);
+			false
+		})
+	}
+
+	fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let signature: Option<Bytes> = self.request(&Request::SignWith {
+			key_type: id.0,
+			crypto_type: key.0 .0,
+			public: Bytes(key.1.clone()),
+			message: Bytes(msg.to_vec()),
+		})?;
+		Ok(signature.map(|signature| signature.0))
+	}
+
+	fn sr25519_vrf_sign(
+		&self,
+		_key_type: KeyTypeId,
+		_public: &sr25519::Public,
+		_transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		// Neither Aura nor GRANDPA signs with VRF.
+		Err(Error::Other("VRF signing is not supported by the remote keystore".into()))
+	}
+
+	fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		let signature: Option<Bytes> = self.request(&Request::EcdsaSignPrehashed {
+			key_type: id.0,
+			public: Bytes(public.to_raw_vec()),
+			message: Bytes(msg.to_vec()),
+		})?;
+		signature
+			.map(|signature| {
+				ecdsa::Signature::try_from(&signature[..]).map_err(|()| {
+					Error::ValidationError("invalid ECDSA signature from the signer".into())
+				})
+			})
+			.transpose()
+	}
+}
+
+#[async_trait]
+impl CryptoStore for RemoteKeystore {
+	async fn sr25519_public_keys(&self, id: KeyTypeId) -> Vec<sr25519::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::sr25519_public_keys(keystore, id))
+			.await
+	}
+
+	async fn sr25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<sr25519::Public, Error> {
+		let seed = seed.map(String::from);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::sr25519_generate_new(keystore, id, seed.as_deref())
+		})
+		.await
+	}
+
+	async fn ed25519_public_keys(&self, id: KeyTypeId) -> Vec<ed25519::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::ed25519_public_keys(keystore, id))
+			.await
+	}
+
+	async fn ed25519_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ed25519::Public, Error> {
+		let seed = seed.map(String::from);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::ed25519_generate_new(keystore, id, seed.as_deref())
+		})
+		.await
+	}
+
+	async fn ecdsa_public_keys(&self, id: KeyTypeId) -> Vec<ecdsa::Public> {
+		self.blocking(move |keystore| SyncCryptoStore::ecdsa_public_keys(keystore, id))
+			.await
+	}
+
+	async fn ecdsa_generate_new(
+		&self,
+		id: KeyTypeId,
+		seed: Option<&str>,
+	) -> Result<ecdsa::Public, Error> {
+		let seed = seed.map(String::from);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::ecdsa_generate_new(keystore, id, seed.as_deref())
+		})
+		.await
+	}
+
+	async fn insert_unknown(&self, id: KeyTypeId, suri: &str, public: &[u8]) -> Result<(), ()> {
+		let (suri, public) = (suri.to_owned(), public.to_vec());
+		self.blocking(move |keystore| SyncCryptoStore::insert_unknown(keystore, id, &suri, &public))
+			.await
+	}
+
+	async fn supported_keys(
+		&self,
+		id: KeyTypeId,
+		keys: Vec<CryptoTypePublicPair>,
+	) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |keystore| SyncCryptoStore::supported_keys(keystore, id, keys))
+			.await
+	}
+
+	async fn keys(&self, id: KeyTypeId) -> Result<Vec<CryptoTypePublicPair>, Error> {
+		self.blocking(move |keystore| SyncCryptoStore::keys(keystore, id)).await
+	}
+
+	async fn has_keys(&self, public_keys: &[(Vec<u8>, KeyTypeId)]) -> bool {
+		let public_keys = public_keys.to_vec();
+		self.blocking(move |keystore| SyncCryptoStore::has_keys(keystore, &public_keys))
+			.await
+	}
+
+	async fn sign_with(
+		&self,
+		id: KeyTypeId,
+		key: &CryptoTypePublicPair,
+		msg: &[u8],
+	) -> Result<Option<Vec<u8>>, Error> {
+		let (key, msg) = (key.clone(), msg.to_vec());
+		self.blocking(move |keystore| SyncCryptoStore::sign_with(keystore, id, &key, &msg))
+			.await
+	}
+
+	async fn sr25519_vrf_sign(
+		&self,
+		key_type: KeyTypeId,
+		public: &sr25519::Public,
+		transcript_data: VRFTranscriptData,
+	) -> Result<Option<VRFSignature>, Error> {
+		// Not sent to the signer, so it does not block.
+		SyncCryptoStore::sr25519_vrf_sign(self, key_type, public, transcript_data)
+	}
+
+	async fn ecdsa_sign_prehashed(
+		&self,
+		id: KeyTypeId,
+		public: &ecdsa::Public,
+		msg: &[u8; 32],
+	) -> Result<Option<ecdsa::Signature>, Error> {
+		let (public, msg) = (*public, *msg);
+		self.blocking(move |keystore| {
+			SyncCryptoStore::ecdsa_sign_prehashed(keystore, id, &public, &msg)
+		})
+		.await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{net::TcpListener, thread};
+
+	use sc_keystore::LocalKeystore;
+	use sp_core::Pair;
+
+	use super::*;
+	use crate::{
+		signer::Signer,
+		transport::{self, Listener},
+	};
+
+	const KEY_TYPE: KeyTypeId = KeyTypeId(*b"test");
+	const TOKEN: &str = "secret";
+
+	/// Serves an empty keystore on a loopback port, and returns its URI.
+	fn start_signer(allow_new_keys: bool) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let uri = format!("http://{}", listener.local_addr().unwrap());
+		let listener = Listener::Tcp(listener);
+		let signer = Signer::new(LocalKeystore::in_memory(), allow_new_keys);
+		thread::spawn(move || loop {
+			let connection = listener.accept().unwrap();
+			transport::serve(connection, Some(TOKEN), |body| signer.respond(body)).unwrap();
+		});
+		uri
+	}
+
+	#[test]
+	fn keys_are_used_through_the_signer() {
+		let keystore = RemoteKeystore::open(&start_signer(true), Some(TOKEN.into())).unwrap();
+
+		let public = SyncCryptoStore::sr25519_generate_new(&keystore, KEY_TYPE, None).unwrap();
+		assert_eq!(SyncCryptoStore::sr25519_public_keys(&keystore, KEY_TYPE), vec![public]);
+		assert!(SyncCryptoStore::has_keys(&keystore, &[(public.to_raw_vec(), KEY_TYPE)]));
+
+		let signature = SyncCryptoStore::sign_with(&keystore, KEY_TYPE, &public.into(), b"msg")
+			.unwrap()
+			.unwrap();
+		let signature = sr25519::Signature::try_from(&signature[..]).unwrap();
+		assert!(sr25519::Pair::verify(&signature, b"msg", &public));
+
+		let pair = ed25519::Pair::from_string("//Alice", None).unwrap();
+		let public = SyncCryptoStore::ed25519_generate_new(&keystore, KEY_TYPE, Some("//Alice"));
+		assert_eq!(public.unwrap(), pair.public());
+		assert_eq!(
+			SyncCryptoStore::insert_unknown(&keystore, KEY_TYPE, "//Alice", &pair.public().0),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn the_signer_requires_the_token() {
+		let uri = start_signer(true);
+		assert!(matches!(RemoteKeystore::open(&uri, None), Err(Error::Unavailable)));
+		assert!(matches!(
+			RemoteKeystore::open(&uri, Some("wrong".into())),
+			Err(Error::Unavailable)
+		));
+	}
+
+	#[test]
+	fn the_signer_refuses_new_keys_unless_allowed() {
+		let keystore = RemoteKeystore::open(&start_signer(false), Some(TOKEN.into())).unwrap();
+
+		assert!(matches!(
+			SyncCryptoStore::sr25519_generate_new(&keystore, KEY_TYPE, None),
+			Err(Error::Other(_))
+		));
+		let pair = ed25519::Pair::from_string("//Alice", None).unwrap();
+		assert_eq!(
+			SyncCryptoStore::insert_unknown(&keystore, KEY_TYPE, "//Alice", &pair.public().0),
+			Err(())
+		);
+		assert!(SyncCryptoStore::ed25519_public_keys(&keystore, KEY_TYPE).is_empty());
+	}
+}
@@ -0,0 +1,20 @@
+//! A keystore keeping the keys in a remote signer, and the signer serving them.
+//!
+//! The node is pointed to the signer with `--keystore-uri`, either `http://<host>:<port>` or
+//! `unix://<socket path>`. Every keystore operation is sent to the signer as a JSON
+//! [`protocol::Request`] in an HTTP/1.1 `POST`, and the signer answers with a JSON
+//! [`protocol::Response`]: the secret keys never leave the signer, which only returns public
+//! keys and signatures. The requests carry a bearer token when the signer requires one, which the
+//! node reads from the [`TOKEN_ENV`] environment variable.
+//!
+//! The `keystore-signer` binary is a reference signer serving a local keystore directory, so the
+//! Aura and GRANDPA keys can live off the validator host.
+
+mod client;
+pub mod protocol;
+mod signer;
+pub mod transport;
+
+pub use client::{RemoteKeystore, TOKEN_ENV};
+pub use signer::Signer;
+pub use transport::Endpoint;
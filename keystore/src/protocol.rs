@@ -0,0 +1,70 @@
+//! The requests sent to the signer and its responses, encoded as JSON.
+
+use serde::{Deserialize, Serialize};
+use sp_core::{crypto::KeyTypeId, Bytes};
+
+/// A key type or crypto type identifier, as its four bytes.
+pub type Id = [u8; 4];
+
+/// A keystore operation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "method", content = "params")]
+pub enum Request {
+	/// The public keys of `key_type`, as `(crypto type, public key)` pairs.
+	Keys { key_type: Id },
+	/// Generates a new key of `key_type` and `crypto_type`, from `seed` when given, and returns
+	/// its public key.
+	Generate { key_type: Id, crypto_type: Id, seed: Option<String> },
+	/// Inserts the key `suri`, of public key `public`, with the key type `key_type`.
+	InsertUnknown { key_type: Id, suri: String, public: Bytes },
+	/// Whether all the `(public key, key type)` pairs are in the keystore.
+	HasKeys { keys: Vec<(Bytes, Id)> },
+	/// Signs `message` with the key `public` of `key_type` and `crypto_type`, if the keystore
+	/// has it.
+	SignWith { key_type: Id, crypto_type: Id, public: Bytes, message: Bytes },
+	/// Signs the 32 bytes prehashed `message` with the ECDSA key `public` of `key_type`, if the
+	/// keystore has it.
+	EcdsaSignPrehashed { key_type: Id, public: Bytes, message: Bytes },
+}
+
+/// The response to a [`Request`].
+///
+/// The value returned is, by request: `Vec<(Id, Bytes)>` for `keys`, `Bytes` for `generate`,
+/// `()` for `insert_unknown`, `bool` for `has_keys` and `Option<Bytes>` for the signatures.
+pub type Response<T> = Result<T, Error>;
+
+/// The error of a failed request, mirroring [`sp_keystore::Error`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Error {
+	/// The key type is not supported.
+	KeyNotSupported(Id),
+	/// The request is invalid.
+	ValidationError(String),
+	/// The keystore is unavailable.
+	Unavailable,
+	/// Any other error.
+	Other(String),
+}
+
+impl From<sp_keystore::Error> for Error {
+	fn from(error: sp_keystore::Error) -> Self {
+		match error {
+			sp_keystore::Error::KeyNotSupported(key_type) => Self::KeyNotSupported(key_type.0),
+			sp_keystore::Error::ValidationError(message) => Self::ValidationError(message),
+			sp_keystore::Error::Unavailable => Self::Unavailable,
+			sp_keystore::Error::Other(message) => Self::Other(message),
+		}
+	}
+}
+
+impl From<Error> for sp_keystore::Error {
+	fn from(error: Error) -> Self {
+		match error {
+			Error::KeyNotSupported(key_type) => Self::KeyNotSupported(KeyTypeId(key_type)),
+			Error::ValidationError(message) => Self::ValidationError(message),
+			Error::Unavailable => Self::Unavailable,
+			Error::Other(message) => Self::Other(message),
+		}
+	}
+}
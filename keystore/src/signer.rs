@@ -0,0 +1,91 @@
+//! The signer side: answering the requests of the remote keystores from a local keystore.
+
+use sc_keystore::LocalKeystore;
+use sp_core::{
+	crypto::{ByteArray, CryptoTypeId, CryptoTypePublicPair, KeyTypeId},
+	ecdsa, ed25519, sr25519, Bytes,
+};
+use sp_keystore::SyncCryptoStore;
+
+use crate::protocol::{Error, Request, Response};
+
+/// Serves the keys of a local keystore.
+///
+/// New keys, generated or inserted, are refused unless allowed: a remote keystore can otherwise
+/// fill the keystore, or replace the keys used by the validator with `author_rotateKeys`.
+pub struct Signer {
+	keystore: LocalKeystore,
+	allow_new_keys: bool,
+}
+
+impl Signer {
+	/// Serves `keystore`, adding new keys to it only if `allow_new_keys`.
+	pub fn new(keystore: LocalKeystore, allow_new_keys: bool) -> Self {
+		Self { keystore, allow_new_keys }
+	}
+
+	/// Answers the JSON encoded request `body` with a JSON encoded response.
+	pub fn respond(&self, body: &[u8]) -> Vec<u8> {
+		let response = serde_json::from_slice(body)
+			.map_err(|e| Error::ValidationError(format!("invalid request: {}", e)))
+			.and_then(|request| self.handle(request));
+		serde_json::to_vec(&response).expect("the responses are always serializable; qed")
+	}
+
+	fn handle(&self, request: Request) -> Response<serde_json::Value> {
+		let keystore = &self.keystore;
+		let value = match request {
+			Request::Generate { .. } | Request::InsertUnknown { .. } if !self.allow_new_keys =>
+				return Err(Error::Other("the signer does not accept new keys".into())),
+			Request::Keys { key_type } => {
+				let keys = SyncCryptoStore::keys(keystore, KeyTypeId(key_type))?;
+				serde_json::to_value(
+					keys.into_iter()
+						.map(|CryptoTypePublicPair(crypto_type, public)| {
+							(crypto_type.0, Bytes(public))
+						})
+						.collect::<Vec<_>>(),
+				)
+			},
+			Request::Generate { key_type, crypto_type, seed } => {
+				let (id, seed) = (KeyTypeId(key_type), seed.as_deref());
+				let public = match CryptoTypeId(crypto_type) {
+					sr25519::CRYPTO_ID => keystore.sr25519_generate_new(id, seed)?.to_raw_vec(),
+					ed25519::CRYPTO_ID => keystore.ed25519_generate_new(id, seed)?.to_raw_vec(),
+					ecdsa::CRYPTO_ID => keystore.ecdsa_generate_new(id, seed)?.to_raw_vec(),
+					_ => return Err(Error::KeyNotSupported(key_type)),
+				};
+				serde_json::to_value(Bytes(public))
+			},
+			Request::InsertUnknown { key_type, suri, public } => {
+				SyncCryptoStore::insert_unknown(keystore, KeyTypeId(key_type), &suri, &public)
+					.map_err(|()| Error::Other("the key could not be inserted".into()))?;
+				serde_json::to_value(())
+			},
+			Request::HasKeys { keys } => {
+				let keys = keys
+					.into_iter()
+					.map(|(public, key_type)| (public.0, KeyTypeId(key_type)))
+					.collect::<Vec<_>>();
+				serde_json::to_value(SyncCryptoStore::has_keys(keystore, &keys))
+			},
+			Request::SignWith { key_type, crypto_type, public, message } => {
+				let key = CryptoTypePublicPair(CryptoTypeId(crypto_type), public.0);
+				let signature =
+					SyncCryptoStore::sign_with(keystore, KeyTypeId(key_type), &key, &message)?;
+				serde_json::to_value(signature.map(Bytes))
+			},
+			Request::EcdsaSignPrehashed { key_type, public, message } => {
+				let public = ecdsa::Public::from_slice(&public)
+					.map_err(|()| Error::ValidationError("invalid ECDSA public key".into()))?;
+				let message: [u8; 32] = message[..].try_into().map_err(|_| {
+					Error::ValidationError("the prehashed message is not 32 bytes".into())
+				})?;
+				let signature =
+					keystore.ecdsa_sign_prehashed(KeyTypeId(key_type), &public, &message)?;
+				serde_json::to_value(signature.map(|signature| Bytes(signature.as_ref().to_vec())))
+			},
+		};
+		value.map_err(|e| Error::Other(e.to_string()))
+	}
+}
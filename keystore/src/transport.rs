@@ -0,0 +1,332 @@
+//! HTTP/1.1 exchanges with the signer, over TCP or a Unix socket.
+//!
+//! Every connection carries a single `POST` request and its response, and is then closed, so
+//! neither side keeps any state between two requests. A signer given a token only serves the
+//! requests carrying it in an `Authorization: Bearer <token>` header.
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::{
+	fmt,
+	io::{self, BufRead, BufReader, Read, Write},
+	net::{TcpListener, TcpStream},
+	path::PathBuf,
+	str::FromStr,
+	time::Duration,
+};
+
+/// The timeout of the reads and writes on a connection.
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// The longest message read, far above any key or signature exchanged.
+const MAX_MESSAGE_LEN: usize = 1024 * 1024;
+
+/// Where the signer is reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+	/// `http://<host>:<port>[<path>]`.
+	Http { address: String, path: String },
+	/// `unix://<socket path>`.
+	Unix(PathBuf),
+}
+
+impl FromStr for Endpoint {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if let Some(rest) = s.strip_prefix("http://") {
+			let (address, path) = rest.find('/').map_or((rest, "/"), |i| rest.split_at(i));
+			if address.is_empty() {
+				return Err(format!("missing the signer address in `{}`", s))
+			}
+			Ok(Self::Http { address: address.into(), path: path.into() })
+		} else if let Some(path) = s.strip_prefix("unix://") {
+			if path.is_empty() {
+				return Err(format!("missing the signer socket path in `{}`", s))
+			}
+			Ok(Self::Unix(path.into()))
+		} else {
+			Err(format!(
+				"invalid signer endpoint `{}`: expected http://<host>:<port> or unix://<path>",
+				s
+			))
+		}
+	}
+}
+
+impl fmt::Display for Endpoint {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Http { address, path } => write!(f, "http://{}{}", address, path),
+			Self::Unix(path) => write!(f, "unix://{}", path.display()),
+		}
+	}
+}
+
+/// A connection between the keystore and the signer.
+pub trait Connection: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Connection for T {}
+
+impl Endpoint {
+	/// Sends `body` to the signer, with the bearer `token` if any, and returns the body of its
+	/// response.
+	pub fn request(&self, body: &[u8], token: Option<&str>) -> io::Result<Vec<u8>> {
+		let (host, path) = match self {
+			Self::Http { address, path } => (address.as_str(), path.as_str()),
+			Self::Unix(_) => ("localhost", "/"),
+		};
+		let mut connection = self.connect()?;
+		write!(connection, "POST {} HTTP/1.1\r\nHost: {}\r\n", path, host)?;
+		if let Some(token) = token {
+			write!(connection, "Authorization: Bearer {}\r\n", token)?;
+		}
+		write!(
+			connection,
+			"Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+			body.len()
+		)?;
+		connection.write_all(body)?;
+		connection.flush()?;
+
+		let response = read_message(&mut connection)?;
+		if response.start_line.split_whitespace().nth(1) != Some("200") {
+			return Err(io::Error::new(
+				io::ErrorKind::Other,
+				format!("unexpected signer response `{}`", response.start_line),
+			))
+		}
+		Ok(response.body)
+	}
+
+	/// Listens for the keystore requests on the endpoint.
+	pub fn listen(&self) -> io::Result<Listener> {
+		match self {
+			Self::Http { address, .. } => TcpListener::bind(address).map(Listener::Tcp),
+			#[cfg(unix)]
+			Self::Unix(path) => UnixListener::bind(path).map(Listener::Unix),
+			#[cfg(not(unix))]
+			Self::Unix(_) => Err(unix_unsupported()),
+		}
+	}
+
+	fn connect(&self) -> io::Result<Box<dyn Connection>> {
+		match self {
+			Self::Http { address, .. } => {
+				let stream = TcpStream::connect(address)?;
+				stream.set_read_timeout(Some(TIMEOUT))?;
+				stream.set_write_timeout(Some(TIMEOUT))?;
+				Ok(Box::new(stream))
+			},
+			#[cfg(unix)]
+			Self::Unix(path) => {
+				let stream = UnixStream::connect(path)?;
+				stream.set_read_timeout(Some(TIMEOUT))?;
+				stream.set_write_timeout(Some(TIMEOUT))?;
+				Ok(Box::new(stream))
+			},
+			#[cfg(not(unix))]
+			Self::Unix(_) => Err(unix_unsupported()),
+		}
+	}
+}
+
+/// The signer side of an [`Endpoint`].
+pub enum Listener {
+	Tcp(TcpListener),
+	#[cfg(unix)]
+	Unix(UnixListener),
+}
+
+impl Listener {
+	/// Waits for the next connection from a keystore.
+	pub fn accept(&self) -> io::Result<Box<dyn Connection>> {
+		match self {
+			Self::Tcp(listener) => {
+				let (stream, _) = listener.accept()?;
+				stream.set_read_timeout(Some(TIMEOUT))?;
+				stream.set_write_timeout(Some(TIMEOUT))?;
+				Ok(Box::new(stream))
+			},
+			#[cfg(unix)]
+			Self::Unix(listener) => {
+				let (stream, _) = listener.accept()?;
+				stream.set_read_timeout(Some(TIMEOUT))?;
+				stream.set_write_timeout(Some(TIMEOUT))?;
+				Ok(Box::new(stream))
+			},
+		}
+	}
+}
+
+/// Reads a request from `connection` and answers it with the body returned by `handle` for the
+/// request body, if the request carries the bearer `token` when one is given.
+pub fn serve(
+	mut connection: Box<dyn Connection>,
+	token: Option<&str>,
+	handle: impl FnOnce(&[u8]) -> Vec<u8>,
+) -> io::Result<()> {
+	let request = read_message(&mut connection)?;
+	let (status, reply) = if !request.start_line.starts_with("POST ") {
+		("405 Method Not Allowed", Vec::new())
+	} else if !is_authorized(request.authorization.as_deref(), token) {
+		("401 Unauthorized", Vec::new())
+	} else {
+		("200 OK", handle(&request.body))
+	};
+	write!(
+		connection,
+		"HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+		 Connection: close\r\n\r\n",
+		status,
+		reply.len()
+	)?;
+	connection.write_all(&reply)?;
+	connection.flush()
+}
+
+/// Whether the `Authorization` header value `authorization` carries the bearer `token`, if any.
+///
+/// The tokens are compared in a time independent of where they differ.
+fn is_authorized(authorization: Option<&str>, token: Option<&str>) -> bool {
+	let token = match token {
+		Some(token) => token,
+		None => return true,
+	};
+	let given = authorization
+		.and_then(|value| value.strip_prefix("Bearer "))
+		.unwrap_or_default();
+	given.len() == token.len() &&
+		given.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// The parts of an HTTP message used by the keystore and the signer.
+#[derive(Debug)]
+struct Message {
+	start_line: String,
+	authorization: Option<String>,
+	body: Vec<u8>,
+}
+
+/// Reads an HTTP message.
+fn read_message(connection: impl Read) -> io::Result<Message> {
+	let mut reader = BufReader::new(connection.take(MAX_MESSAGE_LEN as u64));
+	let mut start_line = String::new();
+	reader.read_line(&mut start_line)?;
+
+	let (mut content_length, mut authorization) = (0, None);
+	loop {
+		let mut line = String::new();
+		if reader.read_line(&mut line)? == 0 {
+			return Err(io::ErrorKind::UnexpectedEof.into())
+		}
+		let line = line.trim_end();
+		if line.is_empty() {
+			break
+		}
+		if let Some((name, value)) = line.split_once(':') {
+			if name.trim().eq_ignore_ascii_case("content-length") {
+				content_length = value.trim().parse().map_err(|_| {
+					io::Error::new(io::ErrorKind::InvalidData, "invalid content length")
+				})?;
+			} else if name.trim().eq_ignore_ascii_case("authorization") {
+				authorization = Some(value.trim().to_owned());
+			}
+		}
+	}
+	if content_length > MAX_MESSAGE_LEN {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "message too long"))
+	}
+
+	let mut body = vec![0; content_length];
+	reader.read_exact(&mut body)?;
+	Ok(Message { start_line: start_line.trim_end().into(), authorization, body })
+}
+
+#[cfg(not(unix))]
+fn unix_unsupported() -> io::Error {
+	io::Error::new(io::ErrorKind::Unsupported, "Unix sockets are not supported on this platform")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn endpoints_are_parsed() {
+		assert_eq!(
+			"http://127.0.0.1:9955".parse(),
+			Ok(Endpoint::Http { address: "127.0.0.1:9955".into(), path: "/".into() })
+		);
+		assert_eq!(
+			"http://signer:80/keystore".parse(),
+			Ok(Endpoint::Http { address: "signer:80".into(), path: "/keystore".into() })
+		);
+		assert_eq!(
+			"unix:///run/signer.sock".parse(),
+			Ok(Endpoint::Unix("/run/signer.sock".into()))
+		);
+		assert!("http://".parse::<Endpoint>().is_err());
+		assert!("unix://".parse::<Endpoint>().is_err());
+		assert!("ws://127.0.0.1:9955".parse::<Endpoint>().is_err());
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn requests_are_served() {
+		let (mut keystore, signer) = UnixStream::pair().unwrap();
+		write!(keystore, "POST / HTTP/1.1\r\ncontent-length: 4\r\nConnection: close\r\n\r\nping")
+			.unwrap();
+
+		serve(Box::new(signer), None, |body| {
+			assert_eq!(body, b"ping");
+			b"pong".to_vec()
+		})
+		.unwrap();
+
+		let response = read_message(keystore).unwrap();
+		assert_eq!(response.start_line, "HTTP/1.1 200 OK");
+		assert_eq!(response.body, b"pong");
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn requests_without_the_token_are_refused() {
+		for authorization in ["", "Authorization: Bearer wrong\r\n", "Authorization: secret\r\n"] {
+			let (mut keystore, signer) = UnixStream::pair().unwrap();
+			write!(keystore, "POST / HTTP/1.1\r\n{}Content-Length: 0\r\n\r\n", authorization)
+				.unwrap();
+
+			serve(Box::new(signer), Some("secret"), |_| unreachable!()).unwrap();
+
+			let response = read_message(keystore).unwrap();
+			assert_eq!(response.start_line, "HTTP/1.1 401 Unauthorized");
+		}
+
+		let (mut keystore, signer) = UnixStream::pair().unwrap();
+		write!(keystore, "POST / HTTP/1.1\r\nauthorization: Bearer secret\r\n\r\n").unwrap();
+		serve(Box::new(signer), Some("secret"), |_| b"pong".to_vec()).unwrap();
+		assert_eq!(read_message(keystore).unwrap().start_line, "HTTP/1.1 200 OK");
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn only_posts_are_served() {
+		let (mut keystore, signer) = UnixStream::pair().unwrap();
+		write!(keystore, "GET / HTTP/1.1\r\n\r\n").unwrap();
+
+		serve(Box::new(signer), None, |_| unreachable!()).unwrap();
+
+		let response = read_message(keystore).unwrap();
+		assert_eq!(response.start_line, "HTTP/1.1 405 Method Not Allowed");
+		assert!(response.body.is_empty());
+	}
+
+	#[test]
+	fn long_messages_are_rejected() {
+		let message = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_MESSAGE_LEN + 1);
+		assert_eq!(
+			read_message(message.as_bytes()).unwrap_err().kind(),
+			io::ErrorKind::InvalidData
+		);
+	}
+}
@@ -1,8 +1,11 @@
 use node_template_runtime::{
-	opaque::SessionKeys, AccountId, BalancesConfig, GenesisConfig, SessionConfig, Signature,
-	SudoConfig, SystemConfig, WASM_BINARY,
+	opaque::SessionKeys, AccountId, Balance, BalancesConfig, BlockNumber, CouncilConfig,
+	GenesisConfig, SessionConfig, Signature, StakingConfig, SudoConfig, SystemConfig,
+	VestingConfig, DAYS, WASM_BINARY,
 };
+use sc_chain_spec::ChainSpecExtension;
 use sc_service::ChainType;
+use serde::{Deserialize, Serialize};
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{sr25519, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
@@ -12,7 +15,56 @@ use sp_runtime::traits::{IdentifyAccount, Verify};
 // const STAGING_TELEMETRY_URL: &str = "wss://telemetry.polkadot.io/submit/";
 
 /// Specialized `ChainSpec`. This is a specialization of the general Substrate ChainSpec type.
-pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig>;
+pub type ChainSpec = sc_service::GenericChainSpec<GenesisConfig, Extensions>;
+
+/// The node settings read from the chain spec, so each network can tune them without a new node
+/// release.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ChainSpecExtension)]
+#[serde(rename_all = "camelCase")]
+pub struct Extensions {
+	/// The finality tuning, the defaults when missing from the chain spec.
+	#[serde(default)]
+	pub finality: FinalityTuning,
+}
+
+/// The GRANDPA tuning of a network.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct FinalityTuning {
+	/// The period of the GRANDPA gossip, in milliseconds.
+	pub gossip_duration_millis: u64,
+	/// The number of blocks between two justifications stored and sent to the syncing peers.
+	pub justification_period: u32,
+	/// Whether the nodes which are not authorities run the GRANDPA observer, which does not
+	/// vote, instead of the full voter.
+	pub observer_enabled: bool,
+	/// The number of blocks the votes are kept behind the best block.
+	pub vote_before_best_block_by: BlockNumber,
+	/// Whether the votes are restricted to the first three quarters of the unfinalized chain.
+	pub vote_three_quarters_of_unfinalized_chain: bool,
+}
+
+impl Default for FinalityTuning {
+	fn default() -> Self {
+		Self {
+			gossip_duration_millis: 333,
+			justification_period: 512,
+			observer_enabled: false,
+			vote_before_best_block_by: 2,
+			vote_three_quarters_of_unfinalized_chain: true,
+		}
+	}
+}
+
+/// The amount bonded by each initial authority.
+const STASH: Balance = 1 << 50;
+
+/// The initial balance of each endowed account.
+const ENDOWMENT: Balance = 1 << 60;
+
+/// A vesting schedule, as `(account, first block, number of blocks, liquid amount)`: the balance
+/// of the account above the liquid amount vests linearly over the given blocks.
+type VestingSchedule = (AccountId, BlockNumber, BlockNumber, Balance);
 
 /// Generate a crypto pair from seed.
 pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
@@ -45,6 +97,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 				wasm_binary,
 				// Initial PoA authorities
 				vec![authority_keys_from_seed("Alice")],
+				// Initial votes
+				vec![],
 				// Sudo account
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
 				// Pre-funded accounts
@@ -56,6 +110,8 @@ pub fn development_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Charlie"),
 					get_account_id_from_seed::<sr25519::Public>("Dave"),
 				],
+				// Vesting schedules
+				vec![],
 				true,
 			)
 		},
@@ -69,7 +125,7 @@ pub fn development_config() -> Result<ChainSpec, String> {
 		// Properties
 		None,
 		// Extensions
-		None,
+		Extensions::default(),
 	))
 }
 
@@ -87,6 +143,19 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 				wasm_binary,
 				// Initial PoA authorities
 				vec![authority_keys_from_seed("Alice"), authority_keys_from_seed("Bob")],
+				// Initial votes
+				vec![
+					(
+						get_account_id_from_seed::<sr25519::Public>("Charlie"),
+						get_account_id_from_seed::<sr25519::Public>("Alice//stash"),
+						STASH / 2,
+					),
+					(
+						get_account_id_from_seed::<sr25519::Public>("Dave"),
+						get_account_id_from_seed::<sr25519::Public>("Bob//stash"),
+						STASH / 2,
+					),
+				],
 				// Sudo account
 				get_account_id_from_seed::<sr25519::Public>("Alice"),
 				// Pre-funded accounts
@@ -104,6 +173,14 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 					get_account_id_from_seed::<sr25519::Public>("Eve//stash"),
 					get_account_id_from_seed::<sr25519::Public>("Ferdie//stash"),
 				],
+				// Vesting schedules: Eve and Ferdie vest 3/4 of their endowment over a year.
+				["Eve", "Ferdie"]
+					.iter()
+					.map(|seed| {
+						let who = get_account_id_from_seed::<sr25519::Public>(seed);
+						(who, 0, 365 * DAYS, ENDOWMENT / 4)
+					})
+					.collect(),
 				true,
 			)
 		},
@@ -117,7 +194,7 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 		None,
 		None,
 		// Extensions
-		None,
+		Extensions::default(),
 	))
 }
 
@@ -139,8 +216,10 @@ pub fn authority_keys_from_seed(seed: &str) -> (AccountId, AccountId, AuraId, Gr
 fn testnet_genesis(
 	wasm_binary: &[u8],
 	initial_authorities: Vec<(AccountId, AccountId, AuraId, GrandpaId)>,
+	initial_votes: Vec<(AccountId, AccountId, Balance)>,
 	root_key: AccountId,
 	endowed_accounts: Vec<AccountId>,
+	vesting: Vec<VestingSchedule>,
 	_enable_println: bool,
 ) -> GenesisConfig {
 	GenesisConfig {
@@ -150,14 +229,27 @@ fn testnet_genesis(
 		},
 		balances: BalancesConfig {
 			// Configure endowed accounts with initial balance of 1 << 60.
-			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
+			balances: endowed_accounts.iter().cloned().map(|k| (k, ENDOWMENT)).collect(),
 		},
+		vesting: VestingConfig { vesting },
 		session: SessionConfig {
+			// The controllers hold the session keys of their stash, the validator id.
 			keys: initial_authorities
 				.iter()
-				.map(|x| (x.0.clone(), x.0.clone(), session_keys(x.2.clone(), x.3.clone())))
+				.map(|x| (x.1.clone(), x.0.clone(), session_keys(x.2.clone(), x.3.clone())))
 				.collect::<Vec<_>>(),
 		},
+		staking: StakingConfig {
+			candidates: initial_authorities
+				.iter()
+				.map(|x| (x.0.clone(), x.1.clone(), STASH))
+				.collect(),
+			votes: initial_votes,
+			minimum_validators: 1,
+			maximum_validators: initial_authorities.len().max(2) as u32,
+			// Test networks have no registrar to judge the identities.
+			require_identity: false,
+		},
 		aura: Default::default(),
 		grandpa: Default::default(),
 		sudo: SudoConfig {
@@ -165,5 +257,12 @@ fn testnet_genesis(
 			key: Some(root_key),
 		},
 		transaction_payment: Default::default(),
+		council: CouncilConfig {
+			// The controllers of the initial authorities form the initial council.
+			members: initial_authorities.iter().map(|x| x.1.clone()).collect(),
+			phantom: Default::default(),
+		},
+		democracy: Default::default(),
+		treasury: Default::default(),
 	}
 }
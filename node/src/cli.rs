@@ -1,4 +1,8 @@
+use crate::service::Sealing;
+use node_template_runtime::BlockNumber;
 use sc_cli::RunCmd;
+use sc_consensus_slots::BackoffAuthoringOnFinalizedHeadLagging;
+use std::num::NonZeroU32;
 
 #[derive(Debug, clap::Parser)]
 pub struct Cli {
@@ -7,6 +11,51 @@ pub struct Cli {
 
 	#[clap(flatten)]
 	pub run: RunCmd,
+
+	/// Seal the blocks without Aura and GRANDPA, for development and testing: `instant` seals a
+	/// block for every transaction, `manual` only on `engine_createBlock` RPC calls, and
+	/// `interval=<ms>` every given milliseconds. The sealed blocks are finalized right away,
+	/// except the manual ones which are finalized on request.
+	#[clap(long)]
+	pub sealing: Option<Sealing>,
+
+	#[clap(flatten)]
+	pub backoff: BackoffParams,
+}
+
+/// The backoff of the block authoring while the finality lags, so the unfinalized chain does not
+/// keep growing when GRANDPA stalls.
+#[derive(Debug, Clone, clap::Args)]
+pub struct BackoffParams {
+	/// Skip authoring slots while the finalized head lags behind the best block. Not available
+	/// with `--sealing`, which does not author with Aura.
+	#[clap(long, conflicts_with = "sealing")]
+	pub backoff_authoring: bool,
+
+	/// The number of unfinalized blocks authored before backing off.
+	#[clap(long, default_value = "50", conflicts_with = "sealing")]
+	pub backoff_unfinalized_slack: BlockNumber,
+
+	/// The most slots skipped between two authored blocks.
+	#[clap(long, default_value = "100", conflicts_with = "sealing")]
+	pub backoff_max_interval: BlockNumber,
+
+	/// The number of unfinalized blocks past the slack for each slot skipped.
+	#[clap(long, default_value = "2", conflicts_with = "sealing")]
+	pub backoff_authoring_bias: NonZeroU32,
+}
+
+impl BackoffParams {
+	/// The backoff strategy to author with, `None` unless `--backoff-authoring` is set.
+	pub fn backoff_authoring_blocks(
+		&self,
+	) -> Option<BackoffAuthoringOnFinalizedHeadLagging<BlockNumber>> {
+		self.backoff_authoring.then(|| BackoffAuthoringOnFinalizedHeadLagging {
+			max_interval: self.backoff_max_interval,
+			unfinalized_slack: self.backoff_unfinalized_slack,
+			authoring_bias: self.backoff_authoring_bias.get(),
+		})
+	}
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -171,8 +171,14 @@ pub fn run() -> sc_cli::Result<()> {
 		},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let sealing = cli.sealing;
+			let backoff_authoring_blocks = cli.backoff.backoff_authoring_blocks();
 			runner.run_node_until_exit(|config| async move {
-				service::new_full(config).map_err(sc_cli::Error::Service)
+				match sealing {
+					Some(sealing) => service::new_manual_seal(config, sealing),
+					None => service::new_full(config, backoff_authoring_blocks),
+				}
+				.map_err(sc_cli::Error::Service)
 			})
 		},
 	}
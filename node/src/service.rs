@@ -1,15 +1,44 @@
 //! Service and ServiceFactory implementation. Specialized wrapper over substrate service.
 
-use node_template_runtime::{self, opaque::Block, RuntimeApi};
-use sc_client_api::{BlockBackend, ExecutorProvider};
-use sc_consensus_aura::{ImportQueueParams, SlotProportion, StartAuraParams};
+use crate::chain_spec::FinalityTuning;
+use futures::{channel::mpsc, prelude::*};
+use node_template_runtime::{self, opaque::Block, BlockNumber, RuntimeApi, UNIT};
+use pallet_staking_ddpos_rpc::DposRuntimeApi;
+use pallet_staking_ddpos_runtime_api::DposMetricsApi;
+use remote_keystore::RemoteKeystore;
+use sc_client_api::{BlockBackend, BlockchainEvents, ExecutorProvider};
+use sc_consensus_aura::{find_pre_digest, ImportQueueParams, SlotProportion, StartAuraParams};
+use sc_consensus_manual_seal::{
+	consensus::{aura::AuraConsensusDataProvider, timestamp::SlotTimestampProvider},
+	EngineCommand, ManualSealParams,
+};
+use sc_consensus_slots::BackoffAuthoringOnFinalizedHeadLagging;
 pub use sc_executor::NativeElseWasmExecutor;
 use sc_finality_grandpa::SharedVoterState;
-use sc_keystore::LocalKeystore;
 use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
-use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use std::{sync::Arc, time::Duration};
+use sc_transaction_pool_api::TransactionPool;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_consensus_aura::{
+	sr25519::{
+		AuthorityId as AuraId, AuthorityPair as AuraPair, AuthoritySignature as AuraSignature,
+	},
+	AuraApi,
+};
+use sp_core::{
+	crypto::{key_types, Ss58Codec},
+	sr25519,
+};
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT},
+};
+use std::{str::FromStr, sync::Arc, time::Duration};
+use substrate_prometheus_endpoint::{
+	register, CounterVec, Gauge, GaugeVec, Opts, PrometheusError, Registry, F64, U64,
+};
 
 // Our native executor instance.
 pub struct ExecutorDispatch;
@@ -35,33 +64,57 @@ pub(crate) type FullClient =
 	sc_service::TFullClient<Block, RuntimeApi, NativeElseWasmExecutor<ExecutorDispatch>>;
 type FullBackend = sc_service::TFullBackend<Block>;
 type FullSelectChain = sc_consensus::LongestChain<FullBackend, Block>;
+type FullPool = sc_transaction_pool::FullPool<Block, FullClient>;
+
+/// How the blocks are sealed when Aura and GRANDPA are replaced by manual seal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sealing {
+	/// A block is sealed, and finalized, for every transaction imported in the pool.
+	Instant,
+	/// Blocks are only sealed, and finalized, on `engine_createBlock` and `engine_finalizeBlock`
+	/// RPC calls.
+	Manual,
+	/// A block is sealed, and finalized, every given milliseconds.
+	Interval(u64),
+}
 
-pub fn new_partial(
+impl FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Ok(match s {
+			"instant" => Self::Instant,
+			"manual" => Self::Manual,
+			_ => s
+				.strip_prefix("interval=")
+				.and_then(|millis| millis.parse().ok())
+				.filter(|millis| *millis > 0)
+				.map(Self::Interval)
+				.ok_or_else(|| {
+					format!("invalid sealing `{}`: expected instant, manual or interval=<ms>", s)
+				})?,
+		})
+	}
+}
+
+/// The components shared by the Aura and GRANDPA service and the manual seal service, without
+/// an import queue.
+///
+/// With `--keystore-uri`, the keystore is the remote signer's, authenticated with the token of the
+/// `KEYSTORE_SIGNER_TOKEN` environment variable.
+fn new_chain_components(
 	config: &Configuration,
 ) -> Result<
 	sc_service::PartialComponents<
 		FullClient,
 		FullBackend,
 		FullSelectChain,
-		sc_consensus::DefaultImportQueue<Block, FullClient>,
-		sc_transaction_pool::FullPool<Block, FullClient>,
-		(
-			sc_finality_grandpa::GrandpaBlockImport<
-				FullBackend,
-				Block,
-				FullClient,
-				FullSelectChain,
-			>,
-			sc_finality_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
-			Option<Telemetry>,
-		),
+		(),
+		FullPool,
+		Option<Telemetry>,
 	>,
 	ServiceError,
 > {
-	if config.keystore_remote.is_some() {
-		return Err(ServiceError::Other("Remote Keystores are not supported.".into()));
-	}
-
 	let telemetry = config
 		.telemetry_endpoints
 		.clone()
@@ -80,7 +133,7 @@ pub fn new_partial(
 		config.runtime_cache_size,
 	);
 
-	let (client, backend, keystore_container, task_manager) =
+	let (client, backend, mut keystore_container, task_manager) =
 		sc_service::new_full_parts::<Block, RuntimeApi, _>(
 			config,
 			telemetry.as_ref().map(|(_, telemetry)| telemetry.handle()),
@@ -88,6 +141,14 @@ pub fn new_partial(
 		)?;
 	let client = Arc::new(client);
 
+	if let Some(uri) = &config.keystore_remote {
+		let token = std::env::var(remote_keystore::TOKEN_ENV).ok();
+		let keystore = RemoteKeystore::open(uri, token).map_err(|e| {
+			ServiceError::Other(format!("Error hooking up remote keystore for {}: {}", uri, e))
+		})?;
+		keystore_container.set_remote_keystore(Arc::new(keystore));
+	}
+
 	let telemetry = telemetry.map(|(worker, telemetry)| {
 		task_manager.spawn_handle().spawn("telemetry", None, worker.run());
 		telemetry
@@ -103,6 +164,51 @@ pub fn new_partial(
 		client.clone(),
 	);
 
+	Ok(sc_service::PartialComponents {
+		client,
+		backend,
+		task_manager,
+		import_queue: (),
+		keystore_container,
+		select_chain,
+		transaction_pool,
+		other: telemetry,
+	})
+}
+
+pub fn new_partial(
+	config: &Configuration,
+) -> Result<
+	sc_service::PartialComponents<
+		FullClient,
+		FullBackend,
+		FullSelectChain,
+		sc_consensus::DefaultImportQueue<Block, FullClient>,
+		FullPool,
+		(
+			sc_finality_grandpa::GrandpaBlockImport<
+				FullBackend,
+				Block,
+				FullClient,
+				FullSelectChain,
+			>,
+			sc_finality_grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
+			Option<Telemetry>,
+		),
+	>,
+	ServiceError,
+> {
+	let sc_service::PartialComponents {
+		client,
+		backend,
+		task_manager,
+		keystore_container,
+		select_chain,
+		transaction_pool,
+		other: telemetry,
+		..
+	} = new_chain_components(config)?;
+
 	let (grandpa_block_import, grandpa_link) = sc_finality_grandpa::block_import(
 		client.clone(),
 		&(client.clone() as Arc<_>),
@@ -149,37 +255,23 @@ pub fn new_partial(
 	})
 }
 
-fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
-	// FIXME: here would the concrete keystore be built,
-	//        must return a concrete type (NOT `LocalKeystore`) that
-	//        implements `CryptoStore` and `SyncCryptoStore`
-	Err("Remote Keystore not supported.")
-}
-
-/// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+/// Builds a new service for a full client, authoring with the `backoff_authoring_blocks`
+/// strategy when given.
+pub fn new_full(
+	mut config: Configuration,
+	backoff_authoring_blocks: Option<BackoffAuthoringOnFinalizedHeadLagging<BlockNumber>>,
+) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
 		mut task_manager,
 		import_queue,
-		mut keystore_container,
+		keystore_container,
 		select_chain,
 		transaction_pool,
 		other: (block_import, grandpa_link, mut telemetry),
 	} = new_partial(&config)?;
 
-	if let Some(url) = &config.keystore_remote {
-		match remote_keystore(url) {
-			Ok(k) => keystore_container.set_remote_keystore(k),
-			Err(e) => {
-				return Err(ServiceError::Other(format!(
-					"Error hooking up remote keystore for {}: {}",
-					url, e
-				)))
-			},
-		};
-	}
 	let grandpa_protocol_name = sc_finality_grandpa::protocol_standard_name(
 		&client.block_hash(0).ok().flatten().expect("Genesis block exists; qed"),
 		&config.chain_spec,
@@ -217,18 +309,24 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 
 	let role = config.role.clone();
 	let force_authoring = config.force_authoring;
-	let backoff_authoring_blocks: Option<()> = None;
 	let name = config.network.node_name.clone();
 	let enable_grandpa = !config.disable_grandpa;
 	let prometheus_registry = config.prometheus_registry().cloned();
+	let finality = sc_chain_spec::get_extension::<FinalityTuning>(config.chain_spec.extensions())
+		.cloned()
+		.unwrap_or_default();
 
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
 
 		Box::new(move |deny_unsafe, _| {
-			let deps =
-				crate::rpc::FullDeps { client: client.clone(), pool: pool.clone(), deny_unsafe };
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				command_sink: None,
+			};
 			crate::rpc::create_full(deps).map_err(Into::into)
 		})
 	};
@@ -246,6 +344,15 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		telemetry: telemetry.as_mut(),
 	})?;
 
+	if let Some(registry) = prometheus_registry.as_ref() {
+		spawn_dpos_metrics(
+			&task_manager,
+			client.clone(),
+			keystore_container.sync_keystore(),
+			registry,
+		)?;
+	}
+
 	if role.is_authority() {
 		let proposer_factory = sc_basic_authorship::ProposerFactory::new(
 			task_manager.spawn_handle(),
@@ -301,31 +408,43 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	// need a keystore, regardless of which protocol we use below.
 	let keystore =
 		if role.is_authority() { Some(keystore_container.sync_keystore()) } else { None };
+	// the observer is only run by the nodes which would not vote anyway.
+	let observer_enabled = finality.observer_enabled && !role.is_authority();
 
 	let grandpa_config = sc_finality_grandpa::Config {
-		// FIXME #1578 make this available through chainspec
-		gossip_duration: Duration::from_millis(333),
-		justification_period: 512,
+		gossip_duration: Duration::from_millis(finality.gossip_duration_millis),
+		justification_period: finality.justification_period,
 		name: Some(name),
-		observer_enabled: false,
+		observer_enabled,
 		keystore,
 		local_role: role,
 		telemetry: telemetry.as_ref().map(|x| x.handle()),
 		protocol_name: grandpa_protocol_name,
 	};
 
-	if enable_grandpa {
+	if enable_grandpa && observer_enabled {
+		// start the GRANDPA observer, which follows the votes to finalize without voting
+		// NOTE: the full voter provides better guarantees of block and vote data availability,
+		// and having most nodes in a network run the observer could lead to finality stalls, so
+		// it is only run when the chain spec enables it.
+		task_manager.spawn_essential_handle().spawn_blocking(
+			"grandpa-observer",
+			None,
+			sc_finality_grandpa::run_grandpa_observer(grandpa_config, grandpa_link, network)?,
+		);
+	} else if enable_grandpa {
 		// start the full GRANDPA voter
-		// NOTE: non-authorities could run the GRANDPA observer protocol, but at
-		// this point the full voter should provide better guarantees of block
-		// and vote data availability than the observer. The observer has not
-		// been tested extensively yet and having most nodes in a network run it
-		// could lead to finality stalls.
+		let mut voting_rule = sc_finality_grandpa::VotingRulesBuilder::new()
+			.add(sc_finality_grandpa::BeforeBestBlockBy(finality.vote_before_best_block_by));
+		if finality.vote_three_quarters_of_unfinalized_chain {
+			voting_rule = voting_rule.add(sc_finality_grandpa::ThreeQuartersOfTheUnfinalizedChain);
+		}
+
 		let grandpa_config = sc_finality_grandpa::GrandpaParams {
 			config: grandpa_config,
 			link: grandpa_link,
 			network,
-			voting_rule: sc_finality_grandpa::VotingRulesBuilder::default().build(),
+			voting_rule: voting_rule.build(),
 			prometheus_registry,
 			shared_voter_state: SharedVoterState::empty(),
 			telemetry: telemetry.as_ref().map(|x| x.handle()),
@@ -343,3 +462,340 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 	network_starter.start_network();
 	Ok(task_manager)
 }
+
+/// Builds a new service for a full client sealing its blocks with `sealing`, instead of Aura and
+/// GRANDPA.
+///
+/// The blocks carry an Aura pre-digest for the slot after the one of their parent, so the runtime
+/// runs as with Aura: the sessions rotate and the DPoS elections run, while the chain time moves
+/// by one slot duration per block.
+pub fn new_manual_seal(
+	config: Configuration,
+	sealing: Sealing,
+) -> Result<TaskManager, ServiceError> {
+	let sc_service::PartialComponents {
+		client,
+		backend,
+		mut task_manager,
+		keystore_container,
+		select_chain,
+		transaction_pool,
+		other: mut telemetry,
+		..
+	} = new_chain_components(&config)?;
+
+	let import_queue = sc_consensus_manual_seal::import_queue(
+		Box::new(client.clone()),
+		&task_manager.spawn_essential_handle(),
+		config.prometheus_registry(),
+	);
+
+	// There is no GRANDPA, and so no warp sync.
+	let (network, system_rpc_tx, network_starter) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue,
+			block_announce_validator_builder: None,
+			warp_sync: None,
+		})?;
+
+	if config.offchain_worker.enabled {
+		sc_service::build_offchain_workers(
+			&config,
+			task_manager.spawn_handle(),
+			client.clone(),
+			network.clone(),
+		);
+	}
+
+	let prometheus_registry = config.prometheus_registry().cloned();
+	let (command_sink, rpc_commands) = mpsc::channel(1024);
+
+	let rpc_extensions_builder = {
+		let client = client.clone();
+		let pool = transaction_pool.clone();
+
+		Box::new(move |deny_unsafe, _| {
+			let deps = crate::rpc::FullDeps {
+				client: client.clone(),
+				pool: pool.clone(),
+				deny_unsafe,
+				command_sink: Some(command_sink.clone()),
+			};
+			crate::rpc::create_full(deps).map_err(Into::into)
+		})
+	};
+
+	let _rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+		network,
+		client: client.clone(),
+		keystore: keystore_container.sync_keystore(),
+		task_manager: &mut task_manager,
+		transaction_pool: transaction_pool.clone(),
+		rpc_builder: rpc_extensions_builder,
+		backend,
+		system_rpc_tx,
+		config,
+		telemetry: telemetry.as_mut(),
+	})?;
+
+	if let Some(registry) = prometheus_registry.as_ref() {
+		spawn_dpos_metrics(
+			&task_manager,
+			client.clone(),
+			keystore_container.sync_keystore(),
+			registry,
+		)?;
+	}
+
+	let seal_command = || EngineCommand::SealNewBlock {
+		create_empty: true,
+		finalize: true,
+		parent_hash: None,
+		sender: None,
+	};
+	let sealing_commands: Box<dyn Stream<Item = _> + Send + Unpin> = match sealing {
+		Sealing::Instant => Box::new(transaction_pool.import_notification_stream().map(|_| {
+			EngineCommand::SealNewBlock {
+				create_empty: false,
+				finalize: true,
+				parent_hash: None,
+				sender: None,
+			}
+		})),
+		Sealing::Manual => Box::new(stream::pending()),
+		Sealing::Interval(millis) => Box::new(Box::pin(stream::unfold((), move |()| async move {
+			futures_timer::Delay::new(Duration::from_millis(millis)).await;
+			Some((seal_command(), ()))
+		}))),
+	};
+	let commands_stream = stream::select(rpc_commands, sealing_commands);
+
+	let proposer_factory = sc_basic_authorship::ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry.as_ref(),
+		telemetry.as_ref().map(|x| x.handle()),
+	);
+
+	let inherents_client = client.clone();
+	let manual_seal = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+		block_import: client.clone(),
+		env: proposer_factory,
+		client: client.clone(),
+		pool: transaction_pool,
+		commands_stream,
+		select_chain,
+		consensus_data_provider: Some(Box::new(AuraConsensusDataProvider::new(client))),
+		create_inherent_data_providers: move |_, ()| {
+			let client = inherents_client.clone();
+			async move {
+				// One slot later than the parent block, whatever the wall-clock time.
+				let timestamp = SlotTimestampProvider::new_aura(client)
+					.map_err(|e| format!("failed to create the timestamp provider: {}", e))?;
+				let slot =
+					sp_consensus_aura::inherents::InherentDataProvider::new(timestamp.slot());
+
+				Ok::<_, Box<dyn std::error::Error + Send + Sync>>((timestamp, slot))
+			}
+		},
+	});
+
+	// the sealing task is considered essential, i.e. if it fails we take down the service with
+	// it.
+	task_manager.spawn_essential_handle().spawn_blocking(
+		"manual-seal",
+		Some("block-authoring"),
+		manual_seal,
+	);
+
+	network_starter.start_network();
+	Ok(task_manager)
+}
+
+/// The DPoS gauges and counters, updated on every new best block.
+struct DposMetrics {
+	elected_validators: Gauge<U64>,
+	candidate_backing: GaugeVec<F64>,
+	local_validator_rank: Gauge<U64>,
+	local_validator_elected: Gauge<U64>,
+	missed_slots: CounterVec<U64>,
+	blocks_until_next_session: Gauge<U64>,
+}
+
+impl DposMetrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			elected_validators: register(
+				Gauge::new(
+					"dpos_elected_validators",
+					"Number of validators elected for the active era",
+				)?,
+				registry,
+			)?,
+			candidate_backing: register(
+				GaugeVec::new(
+					Opts::new(
+						"dpos_candidate_backing",
+						"Own bond plus the votes received by each candidate, in whole units",
+					),
+					&["candidate"],
+				)?,
+				registry,
+			)?,
+			local_validator_rank: register(
+				Gauge::new(
+					"dpos_local_validator_rank",
+					"Rank of the local validator among the candidates, 0 when not a candidate",
+				)?,
+				registry,
+			)?,
+			local_validator_elected: register(
+				Gauge::new(
+					"dpos_local_validator_elected",
+					"Whether the local validator is elected for the active era",
+				)?,
+				registry,
+			)?,
+			missed_slots: register(
+				CounterVec::new(
+					Opts::new(
+						"dpos_missed_slots_total",
+						"Number of slots in which each authority did not author a block",
+					),
+					&["authority"],
+				)?,
+				registry,
+			)?,
+			blocks_until_next_session: register(
+				Gauge::new(
+					"dpos_blocks_until_next_session",
+					"Number of blocks until the next session change",
+				)?,
+				registry,
+			)?,
+		})
+	}
+
+	/// Updates the metrics for the new best block `header`, counting the slots missed since its
+	/// parent when it extends the previous best block.
+	fn update(
+		&self,
+		client: &FullClient,
+		keystore: &dyn SyncCryptoStore,
+		header: &<Block as BlockT>::Header,
+		extends_best: bool,
+	) -> sp_blockchain::Result<()> {
+		let api = client.runtime_api();
+		let at = BlockId::Hash(header.hash());
+
+		let candidates = api.candidates(&at)?;
+		let validators = api.current_validators(&at)?;
+		self.elected_validators.set(validators.len() as u64);
+		self.candidate_backing.reset();
+		for candidate in &candidates {
+			self.candidate_backing
+				.with_label_values(&[&candidate.who.to_ss58check()])
+				.set(candidate.total as f64 / UNIT as f64);
+		}
+
+		let local_keys = SyncCryptoStore::sr25519_public_keys(keystore, key_types::AURA)
+			.into_iter()
+			.map(AuraId::from)
+			.collect();
+		let local_validators =
+			api.aura_key_owners(&at, local_keys)?.into_iter().flatten().collect::<Vec<_>>();
+		let rank = candidates
+			.iter()
+			.position(|candidate| local_validators.contains(&candidate.who))
+			.map_or(0, |index| index as u64 + 1);
+		self.local_validator_rank.set(rank);
+		self.local_validator_elected
+			.set(validators.iter().any(|validator| local_validators.contains(validator)) as u64);
+
+		self.blocks_until_next_session.set(api.blocks_until_next_session(&at)?.into());
+
+		if extends_best {
+			self.count_missed_slots(client, header)?;
+		}
+		Ok(())
+	}
+
+	/// Counts the slots between the parent of `header` and `header`, missed by their authors.
+	fn count_missed_slots(
+		&self,
+		client: &FullClient,
+		header: &<Block as BlockT>::Header,
+	) -> sp_blockchain::Result<()> {
+		let parent = match client.header(BlockId::Hash(*header.parent_hash()))? {
+			Some(parent) => parent,
+			None => return Ok(()),
+		};
+		let (slot, parent_slot) = match (
+			find_pre_digest::<Block, AuraSignature>(header),
+			find_pre_digest::<Block, AuraSignature>(&parent),
+		) {
+			(Ok(slot), Ok(parent_slot)) => (*slot, *parent_slot),
+			// the genesis block has no slot.
+			_ => return Ok(()),
+		};
+
+		let api = client.runtime_api();
+		let at = BlockId::Hash(parent.hash());
+		let authorities = api.authorities(&at)?;
+		if authorities.is_empty() {
+			return Ok(())
+		}
+		let owners = api.aura_key_owners(&at, authorities.clone())?;
+
+		// the author of `slot` is `authorities[slot % n]`, so each authority missed `missed / n`
+		// slots, plus one when it is among the `missed % n` authorities after the parent's.
+		let n = authorities.len() as u64;
+		let missed = slot.saturating_sub(parent_slot + 1);
+		let first = (parent_slot + 1) % n;
+		for (index, (key, owner)) in authorities.into_iter().zip(owners).enumerate() {
+			let count = missed / n + u64::from((index as u64 + n - first) % n < missed % n);
+			if count > 0 {
+				let authority = owner.map_or_else(
+					|| sr25519::Public::from(key).to_ss58check(),
+					|owner| owner.to_ss58check(),
+				);
+				self.missed_slots.with_label_values(&[&authority]).inc_by(count);
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Spawns the task exporting the DPoS metrics to `registry`, updated on every new best block.
+///
+/// The task runs on a blocking thread: the local keys may be fetched from a remote signer, and
+/// the runtime calls read the state from the database.
+fn spawn_dpos_metrics(
+	task_manager: &TaskManager,
+	client: Arc<FullClient>,
+	keystore: SyncCryptoStorePtr,
+	registry: &Registry,
+) -> Result<(), PrometheusError> {
+	let metrics = DposMetrics::register(registry)?;
+	let mut best_blocks = client
+		.import_notification_stream()
+		.filter(|notification| future::ready(notification.is_new_best));
+
+	task_manager.spawn_handle().spawn_blocking("dpos-metrics", None, async move {
+		let mut best_hash = client.info().best_hash;
+		while let Some(notification) = best_blocks.next().await {
+			let extends_best = *notification.header.parent_hash() == best_hash;
+			best_hash = notification.hash;
+			if let Err(e) = metrics.update(&client, &*keystore, &notification.header, extends_best)
+			{
+				log::debug!(target: "dpos-metrics", "Updating the DPoS metrics failed: {}", e);
+			}
+		}
+	});
+	Ok(())
+}
@@ -0,0 +1,138 @@
+//! RPC interface for the DPoS staking pallet.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpsee::{
+	core::{async_trait, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_staking_ddpos::CandidateInfo;
+pub use pallet_staking_ddpos_runtime_api::DposApi as DposRuntimeApi;
+
+#[rpc(client, server)]
+pub trait DposApi<BlockHash, AccountId, Balance> {
+	/// All the bonded candidates with their backing, ordered by rank.
+	#[method(name = "dpos_candidates")]
+	fn candidates(
+		&self,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<CandidateInfo<AccountId, Balance>>>;
+
+	/// The votes cast by `voter`, as `(candidate, amount)` pairs.
+	#[method(name = "dpos_votes")]
+	fn votes(
+		&self,
+		voter: AccountId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<(AccountId, Balance)>>;
+
+	/// The validators of the active era.
+	#[method(name = "dpos_currentValidators")]
+	fn current_validators(&self, at: Option<BlockHash>) -> RpcResult<Vec<AccountId>>;
+
+	/// The validators of the latest planned era.
+	#[method(name = "dpos_nextValidators")]
+	fn next_validators(&self, at: Option<BlockHash>) -> RpcResult<Vec<AccountId>>;
+
+	/// The minimum and maximum number of validators to elect.
+	#[method(name = "dpos_validatorCount")]
+	fn validator_count(&self, at: Option<BlockHash>) -> RpcResult<(u32, u32)>;
+}
+
+/// Provides RPC methods to query the DPoS staking state.
+pub struct Dpos<C, P> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<P>,
+}
+
+impl<C, P> Dpos<C, P> {
+	/// Creates a new instance of the Dpos RPC helper.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+fn runtime_error(message: &'static str, e: impl std::fmt::Debug) -> jsonrpsee::core::Error {
+	CallError::Custom(ErrorObject::owned(
+		Error::RuntimeError.into(),
+		message,
+		Some(format!("{:?}", e)),
+	))
+	.into()
+}
+
+#[async_trait]
+impl<C, Block, AccountId, Balance> DposApiServer<<Block as BlockT>::Hash, AccountId, Balance>
+	for Dpos<C, Block>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: DposRuntimeApi<Block, AccountId, Balance>,
+	AccountId: Codec + Send + Sync + 'static,
+	Balance: Codec + Send + Sync + 'static,
+{
+	fn candidates(
+		&self,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Vec<CandidateInfo<AccountId, Balance>>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.candidates(&at).map_err(|e| runtime_error("Unable to query candidates.", e))
+	}
+
+	fn votes(
+		&self,
+		voter: AccountId,
+		at: Option<Block::Hash>,
+	) -> RpcResult<Vec<(AccountId, Balance)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.votes_of(&at, voter).map_err(|e| runtime_error("Unable to query votes.", e))
+	}
+
+	fn current_validators(&self, at: Option<Block::Hash>) -> RpcResult<Vec<AccountId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.current_validators(&at)
+			.map_err(|e| runtime_error("Unable to query current validators.", e))
+	}
+
+	fn next_validators(&self, at: Option<Block::Hash>) -> RpcResult<Vec<AccountId>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.next_validators(&at)
+			.map_err(|e| runtime_error("Unable to query next validators.", e))
+	}
+
+	fn validator_count(&self, at: Option<Block::Hash>) -> RpcResult<(u32, u32)> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.validator_count(&at)
+			.map_err(|e| runtime_error("Unable to query validator count.", e))
+	}
+}
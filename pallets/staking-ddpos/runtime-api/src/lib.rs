@@ -0,0 +1,53 @@
+//! Runtime API definition for the DPoS staking pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+pub use pallet_staking_ddpos::CandidateInfo;
+
+sp_api::decl_runtime_apis! {
+	/// Queries over the state of the DPoS staking pallet.
+	pub trait DposApi<AccountId, Balance> where
+		AccountId: Codec,
+		Balance: Codec,
+	{
+		/// All the bonded candidates with their backing, ordered by rank.
+		fn candidates() -> Vec<CandidateInfo<AccountId, Balance>>;
+
+		/// The votes cast by `voter`, as `(candidate, amount)` pairs.
+		fn votes_of(voter: AccountId) -> Vec<(AccountId, Balance)>;
+
+		/// The validators of the active era.
+		fn current_validators() -> Vec<AccountId>;
+
+		/// The validators of the latest planned era.
+		fn next_validators() -> Vec<AccountId>;
+
+		/// The minimum and maximum number of validators to elect.
+		fn validator_count() -> (u32, u32);
+	}
+
+	/// The transaction fee multiplier, adjusted to the block fullness to price out the floods of
+	/// staking transactions.
+	pub trait TransactionFeeApi<Multiplier> where
+		Multiplier: Codec,
+	{
+		/// The multiplier applied to the weight fee of the transactions in the next block.
+		fn next_fee_multiplier() -> Multiplier;
+	}
+
+	/// The DPoS state exported in the node metrics which is not in [`DposApi`].
+	pub trait DposMetricsApi<AccountId, AuraId, BlockNumber> where
+		AccountId: Codec,
+		AuraId: Codec,
+		BlockNumber: Codec,
+	{
+		/// The validator which set each Aura session key, if any.
+		fn aura_key_owners(keys: Vec<AuraId>) -> Vec<Option<AccountId>>;
+
+		/// The number of blocks until the next session change.
+		fn blocks_until_next_session() -> BlockNumber;
+	}
+}
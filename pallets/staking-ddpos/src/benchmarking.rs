@@ -0,0 +1,164 @@
+//! Benchmarking setup for pallet-staking-ddpos
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as Staking;
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+
+const SEED: u32 = 0;
+
+fn funded_account<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+	let who = account(name, index, SEED);
+	let balance = BalanceOf::<T>::from(1_000_000_000u64).max(T::MinVote::get() * 10u32.into());
+	T::Currency::make_free_balance_be(&who, balance);
+	who
+}
+
+benchmarks! {
+	bond {
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::from(1_000_000_000u64));
+		let value = BalanceOf::<T>::from(1_000u64);
+	}: _(RawOrigin::Signed(caller.clone()), value)
+	verify {
+		assert_eq!(Candidates::<T>::get(&caller), Some(value));
+	}
+
+	unbond {
+		let caller = funded_account::<T>("candidate", 0);
+		Staking::<T>::bond(RawOrigin::Signed(caller.clone()).into(), 1_000u64.into())?;
+	}: _(RawOrigin::Signed(caller.clone()), 1_000u64.into())
+	verify {
+		assert!(!Candidates::<T>::contains_key(&caller));
+	}
+
+	withdraw_unbonded {
+		let caller = funded_account::<T>("candidate", 0);
+		Staking::<T>::bond(RawOrigin::Signed(caller.clone()).into(), 1_000u64.into())?;
+		Staking::<T>::unbond(RawOrigin::Signed(caller.clone()).into(), 1_000u64.into())?;
+		ActiveEra::<T>::put(T::BondingDuration::get());
+	}: _(RawOrigin::Signed(caller.clone()))
+	verify {
+		assert!(!Unlocking::<T>::contains_key(&caller));
+	}
+
+	rebond {
+		let caller = funded_account::<T>("candidate", 0);
+		Staking::<T>::bond(RawOrigin::Signed(caller.clone()).into(), 1_000u64.into())?;
+		Staking::<T>::unbond(RawOrigin::Signed(caller.clone()).into(), 1_000u64.into())?;
+	}: _(RawOrigin::Signed(caller.clone()), 1_000u64.into())
+	verify {
+		assert_eq!(Candidates::<T>::get(&caller), Some(1_000u64.into()));
+	}
+
+	set_controller {
+		let caller = funded_account::<T>("candidate", 0);
+		Staking::<T>::bond(RawOrigin::Signed(caller.clone()).into(), 1_000u64.into())?;
+		let controller: T::AccountId = account("controller", 0, SEED);
+	}: _(RawOrigin::Signed(caller.clone()), controller.clone())
+	verify {
+		assert_eq!(Bonded::<T>::get(&caller), Some(controller));
+	}
+
+	set_commission {
+		let caller = funded_account::<T>("candidate", 0);
+		Staking::<T>::bond(RawOrigin::Signed(caller.clone()).into(), 1_000u64.into())?;
+		let commission = Perbill::from_percent(10);
+	}: _(RawOrigin::Signed(caller.clone()), commission)
+	verify {
+		assert!(PendingCommission::<T>::contains_key(&caller));
+	}
+
+	vote {
+		let candidate = funded_account::<T>("candidate", 0);
+		Staking::<T>::bond(RawOrigin::Signed(candidate.clone()).into(), 1_000u64.into())?;
+		let caller = funded_account::<T>("voter", 0);
+		let value = T::MinVote::get();
+	}: _(RawOrigin::Signed(caller.clone()), candidate.clone(), value)
+	verify {
+		assert_eq!(Votes::<T>::get(&caller, &candidate), Some(value));
+	}
+
+	unvote {
+		let candidate = funded_account::<T>("candidate", 0);
+		Staking::<T>::bond(RawOrigin::Signed(candidate.clone()).into(), 1_000u64.into())?;
+		let caller = funded_account::<T>("voter", 0);
+		Staking::<T>::vote(
+			RawOrigin::Signed(caller.clone()).into(),
+			candidate.clone(),
+			T::MinVote::get(),
+		)?;
+	}: _(RawOrigin::Signed(caller.clone()), candidate.clone())
+	verify {
+		assert!(!Votes::<T>::contains_key(&caller, &candidate));
+	}
+
+	payout_stakers {
+		let n in 0 .. T::MaxVotersPerCandidate::get();
+		let validator = funded_account::<T>("candidate", 0);
+		Staking::<T>::bond(RawOrigin::Signed(validator.clone()).into(), 1_000u64.into())?;
+		for i in 0..n {
+			let voter = funded_account::<T>("voter", i);
+			let value = T::MinVote::get();
+			Staking::<T>::vote(RawOrigin::Signed(voter).into(), validator.clone(), value)?;
+		}
+		let (_, exposure) = Staking::<T>::elect().expect("one candidate is bonded; qed").remove(0);
+		ErasStakers::<T>::insert(0, &validator, exposure);
+		let reward = BalanceOf::<T>::from(1_000_000u64);
+		ErasValidatorReward::<T>::insert(0, &validator, reward);
+		ErasValidatorCommission::<T>::insert(0, &validator, Perbill::from_percent(10));
+		UnclaimedRewards::<T>::put(reward);
+		let pot = Staking::<T>::reward_pot();
+		T::Currency::make_free_balance_be(&pot, T::Currency::minimum_balance() + reward);
+		let caller: T::AccountId = whitelisted_caller();
+	}: _(RawOrigin::Signed(caller), validator.clone(), 0)
+	verify {
+		assert!(!ErasValidatorReward::<T>::contains_key(0, &validator));
+		assert!(UnclaimedRewards::<T>::get().is_zero());
+	}
+
+	submit_election_solution {
+		let c in 1 .. 100;
+		let v in 0 .. T::MaxVotersPerCandidate::get();
+		for i in 0..c {
+			let candidate = funded_account::<T>("candidate", i);
+			Staking::<T>::bond(RawOrigin::Signed(candidate).into(), 1_000u64.into())?;
+		}
+		let candidate = account::<T::AccountId>("candidate", 0, SEED);
+		for i in 0..v {
+			let voter = funded_account::<T>("voter", i);
+			let value = T::MinVote::get();
+			Staking::<T>::vote(RawOrigin::Signed(voter).into(), candidate.clone(), value)?;
+		}
+		let solution = Staking::<T>::compute_solution().expect("a candidate is bonded; qed");
+	}: _(RawOrigin::None, solution)
+	verify {
+		assert!(QueuedSolution::<T>::exists());
+	}
+
+	set_minimum_validators {
+		let origin = T::AdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, 1)
+	verify {
+		assert_eq!(MinimumValidators::<T>::get(), 1);
+	}
+
+	set_maximum_validators {
+		let origin = T::AdminOrigin::successful_origin();
+		let count = MinimumValidators::<T>::get();
+	}: _<T::Origin>(origin, count)
+	verify {
+		assert_eq!(MaximumValidators::<T>::get(), count);
+	}
+
+	set_require_identity {
+		let origin = T::AdminOrigin::successful_origin();
+	}: _<T::Origin>(origin, true)
+	verify {
+		assert!(RequireIdentity::<T>::get());
+	}
+
+	impl_benchmark_test_suite!(Staking, crate::mock::new_test_ext(), crate::mock::Test);
+}
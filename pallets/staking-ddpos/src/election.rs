@@ -0,0 +1,256 @@
+//! Offchain election of the validator set.
+//!
+//! Electing the validators needs to iterate over all the candidates and votes, which does not fit
+//! in a block once the candidate and voter sets grow. Validators running an offchain worker
+//! compute the election when the next era is about to be planned, and submit it as an unsigned
+//! [`ElectionSolution`]. The runtime checks the solution against the stored bonds and votes, which
+//! only reads the backing of each candidate and the votes of the elected validators, and queues
+//! the best scoring one. The bonds and votes of the winners changing in the meantime are applied
+//! to the queued solution, which is only dropped when the change can alter the elected set. When
+//! the era is planned the queued solution is checked again, and the on-chain election is used if
+//! there is no valid one.
+//!
+//! Votes are cast for a single candidate, so the stake-weighted election is the set of the best
+//! backed candidates: a solution must elect as many validators as the on-chain election would,
+//! and the best solution maximizes the minimal backing, then the total backing.
+
+use super::*;
+use frame_support::ensure;
+use frame_system::offchain::SubmitTransaction;
+use sp_runtime::transaction_validity::{
+	InvalidTransaction, TransactionSource, TransactionValidity, ValidTransaction,
+};
+
+/// The score of an election solution. Higher is better, comparing the minimal backing first.
+#[derive(
+	Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug, TypeInfo, Default,
+)]
+pub struct ElectionScore<Balance> {
+	/// The backing of the least backed elected validator.
+	pub minimal_stake: Balance,
+	/// The total backing of the elected validators.
+	pub sum_stake: Balance,
+}
+
+/// A validator set elected off-chain for an era, with the exposure of each validator.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct ElectionSolution<AccountId, Balance> {
+	/// The era the validators are elected for.
+	pub era: EraIndex,
+	/// The elected validators with their exposure. The voters of each exposure are sorted by
+	/// account.
+	pub winners: Vec<(AccountId, Exposure<AccountId, Balance>)>,
+	/// The score of the solution.
+	pub score: ElectionScore<Balance>,
+}
+
+impl<AccountId, Balance> ElectionSolution<AccountId, Balance> {
+	/// The number of votes backing the elected validators.
+	pub fn vote_count(&self) -> u32 {
+		self.winners.iter().map(|(_, exposure)| exposure.others.len() as u32).sum()
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The era the next election is for.
+	pub fn next_era() -> EraIndex {
+		Self::current_era().map_or(0, |era| era.saturating_add(1))
+	}
+
+	/// Whether the next era is planned at the next session.
+	pub fn election_due() -> bool {
+		match Self::current_era() {
+			Some(era) => {
+				let start = Self::eras_start_session_index(era).unwrap_or_default();
+				// The session after the next one is planned when the next one starts.
+				Self::current_session().saturating_add(2).saturating_sub(start) >=
+					T::SessionsPerEra::get()
+			},
+			None => true,
+		}
+	}
+
+	/// Compute the election for the next era, as the on-chain election would.
+	pub fn compute_solution() -> Option<ElectionSolution<T::AccountId, BalanceOf<T>>> {
+		let winners = Self::elect()?;
+		let score = Self::score(&winners);
+		Some(ElectionSolution { era: Self::next_era(), winners, score })
+	}
+
+	/// Check `solution` against the current bonds and votes, and against the queued solution.
+	pub fn check_solution(
+		solution: &ElectionSolution<T::AccountId, BalanceOf<T>>,
+	) -> Result<(), Error<T>> {
+		ensure!(solution.era == Self::next_era(), Error::<T>::WrongEra);
+
+		let expected = Self::maximum_validators().min(Candidates::<T>::count());
+		ensure!(
+			expected >= Self::minimum_validators() && solution.winners.len() == expected as usize,
+			Error::<T>::WrongSolutionSize
+		);
+
+		let mut winners = solution.winners.iter().map(|(who, _)| who).collect::<Vec<_>>();
+		winners.sort();
+		ensure!(winners.windows(2).all(|w| w[0] != w[1]), Error::<T>::InvalidSolution);
+
+		for (who, exposure) in &solution.winners {
+			let bond = Candidates::<T>::get(who).ok_or(Error::<T>::InvalidSolution)?;
+			ensure!(exposure.own == bond, Error::<T>::InvalidSolution);
+			ensure!(
+				exposure.others.windows(2).all(|w| w[0].0 < w[1].0),
+				Error::<T>::InvalidSolution
+			);
+
+			let mut backing = BalanceOf::<T>::zero();
+			for (voter, value) in &exposure.others {
+				ensure!(Votes::<T>::get(voter, who) == Some(*value), Error::<T>::InvalidSolution);
+				backing = backing.saturating_add(*value);
+			}
+			ensure!(backing == VotesReceived::<T>::get(who), Error::<T>::InvalidSolution);
+			ensure!(exposure.total == bond.saturating_add(backing), Error::<T>::InvalidSolution);
+		}
+		ensure!(Self::score(&solution.winners) == solution.score, Error::<T>::InvalidSolution);
+
+		// No candidate left out can be backed more than the least backed winner.
+		let mut totals = Candidates::<T>::iter()
+			.map(|(who, bond)| bond.saturating_add(VotesReceived::<T>::get(&who)))
+			.collect::<Vec<_>>();
+		totals.sort_unstable_by(|a, b| b.cmp(a));
+		ensure!(
+			totals
+				.get(expected as usize - 1)
+				.map_or(true, |kth| solution.score.minimal_stake >= *kth),
+			Error::<T>::SuboptimalSolution
+		);
+
+		if let Some(queued) = QueuedSolution::<T>::get() {
+			ensure!(
+				queued.era != solution.era || solution.score > queued.score,
+				Error::<T>::WeakSolution
+			);
+		}
+		Ok(())
+	}
+
+	/// Apply a change of the backing of `candidate`, and of the vote of `voter` for it if any, to
+	/// the queued solution.
+	///
+	/// The exposure and the score are updated when `candidate` is a winner, so that a change to
+	/// the backing of a winner does not discard the solution. The solution is dropped when the
+	/// change can alter the elected set: a winner leaving the candidate pool, or a candidate left
+	/// out becoming backed more than the least backed winner.
+	pub(crate) fn update_queued_solution(candidate: &T::AccountId, voter: Option<&T::AccountId>) {
+		let mut solution = match QueuedSolution::<T>::get() {
+			Some(solution) if solution.era == Self::next_era() => solution,
+			_ => return,
+		};
+		let expected = Self::maximum_validators().min(Candidates::<T>::count());
+		if solution.winners.len() != expected as usize {
+			return QueuedSolution::<T>::kill()
+		}
+		let minimal_stake = solution.score.minimal_stake;
+		let total = match Candidates::<T>::get(candidate) {
+			Some(bond) => bond.saturating_add(VotesReceived::<T>::get(candidate)),
+			None => BalanceOf::<T>::zero(),
+		};
+
+		let exposure = match solution.winners.iter_mut().find(|(who, _)| who == candidate) {
+			Some((_, exposure)) => exposure,
+			None => {
+				if total > minimal_stake {
+					QueuedSolution::<T>::kill();
+				}
+				return
+			},
+		};
+		// A winner now backed less than the least backed winner was may be overtaken by a candidate
+		// left out.
+		match Candidates::<T>::get(candidate) {
+			Some(bond) if total >= minimal_stake => {
+				exposure.own = bond;
+				exposure.total = total;
+			},
+			_ => return QueuedSolution::<T>::kill(),
+		}
+		if let Some(voter) = voter {
+			let position = exposure.others.binary_search_by(|(who, _)| who.cmp(voter));
+			match (position, Votes::<T>::get(voter, candidate)) {
+				(Ok(i), Some(value)) => exposure.others[i].1 = value,
+				(Ok(i), None) => {
+					exposure.others.remove(i);
+				},
+				(Err(i), Some(value)) => exposure.others.insert(i, (voter.clone(), value)),
+				(Err(_), None) => (),
+			}
+		}
+
+		solution.score = Self::score(&solution.winners);
+		QueuedSolution::<T>::put(solution);
+	}
+
+	/// Compute the election and submit it, if the next era is planned at the next session.
+	pub(crate) fn offchain_election() {
+		if !Self::election_due() {
+			return
+		}
+		let solution = match Self::compute_solution() {
+			Some(solution) => solution,
+			None => return,
+		};
+		if let Some(queued) = QueuedSolution::<T>::get() {
+			if queued.era == solution.era && queued.score >= solution.score {
+				return
+			}
+		}
+
+		log::debug!(target: LOG_TARGET, "submitting the election for era {}", solution.era);
+		let call = Call::submit_election_solution { solution };
+		if SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into()).is_err() {
+			log::error!(target: LOG_TARGET, "failed to submit the election solution");
+		}
+	}
+
+	/// Take the queued solution for the next era if it is still valid, or elect on-chain.
+	pub(crate) fn elect_next_era() -> Option<Vec<ElectedValidator<T>>> {
+		if let Some(solution) = QueuedSolution::<T>::take() {
+			match Self::check_solution(&solution) {
+				Ok(()) => return Some(solution.winners),
+				Err(e) => log::warn!(
+					target: LOG_TARGET,
+					"queued election solution is no longer valid ({:?}), electing on-chain",
+					e
+				),
+			}
+		}
+		Self::elect()
+	}
+
+	pub(crate) fn validate_solution(
+		source: TransactionSource,
+		solution: &ElectionSolution<T::AccountId, BalanceOf<T>>,
+	) -> TransactionValidity {
+		// Only the solutions computed by the local offchain worker, or included in a block.
+		if !matches!(source, TransactionSource::Local | TransactionSource::InBlock) {
+			return InvalidTransaction::Call.into()
+		}
+		Self::check_solution(solution).map_err(|e| {
+			log::debug!(target: LOG_TARGET, "rejecting the election solution: {:?}", e);
+			InvalidTransaction::Call
+		})?;
+
+		ValidTransaction::with_tag_prefix("DposElection")
+			.priority(T::UnsignedPriority::get())
+			.and_provides((solution.era, solution.score))
+			.propagate(false)
+			.build()
+	}
+
+	fn score(winners: &[ElectedValidator<T>]) -> ElectionScore<BalanceOf<T>> {
+		let minimal_stake =
+			winners.iter().map(|(_, exposure)| exposure.total).min().unwrap_or_default();
+		let sum_stake = winners
+			.iter()
+			.fold(BalanceOf::<T>::zero(), |sum, (_, exposure)| sum.saturating_add(exposure.total));
+		ElectionScore { minimal_stake, sum_stake }
+	}
+}
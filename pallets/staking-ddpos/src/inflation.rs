@@ -0,0 +1,67 @@
+//! Inflation minted at the end of every era.
+//!
+//! The yearly inflation follows a curve of the staking ratio, the stake bonded by all the
+//! candidates and voters over the total issuance, so that the inflation rises while less than the
+//! ideal ratio is staked. The curve is usually built with `pallet_staking_reward_curve::build!`,
+//! from the minimum and maximum inflation, the ideal staking ratio and the decay of the inflation
+//! past the ideal ratio.
+
+use sp_runtime::{curve::PiecewiseLinear, traits::AtLeast32BitUnsigned, Perbill};
+use sp_std::marker::PhantomData;
+
+/// Milliseconds per Julian year (365.25 days).
+const MILLISECONDS_PER_YEAR: u64 = 1000 * 3600 * 24 * 36525 / 100;
+
+/// Computes the amount minted at the end of an era.
+pub trait EraPayout<Balance> {
+	/// The amount to mint for an era that lasted `era_duration_millis`, given the total bonded
+	/// stake and the total issuance.
+	fn era_payout(
+		total_staked: Balance,
+		total_issuance: Balance,
+		era_duration_millis: u64,
+	) -> Balance;
+}
+
+impl<Balance: Default> EraPayout<Balance> for () {
+	fn era_payout(
+		_total_staked: Balance,
+		_total_issuance: Balance,
+		_era_duration_millis: u64,
+	) -> Balance {
+		Default::default()
+	}
+}
+
+/// An [`EraPayout`] following the yearly inflation curve `T`.
+pub struct ConvertCurve<T>(PhantomData<T>);
+
+impl<Balance, T> EraPayout<Balance> for ConvertCurve<T>
+where
+	Balance: AtLeast32BitUnsigned + Clone,
+	T: frame_support::traits::Get<&'static PiecewiseLinear<'static>>,
+{
+	fn era_payout(
+		total_staked: Balance,
+		total_issuance: Balance,
+		era_duration_millis: u64,
+	) -> Balance {
+		compute_total_payout(T::get(), total_staked, total_issuance, era_duration_millis)
+	}
+}
+
+/// The share of the yearly inflation `yearly_inflation`, at the staking ratio
+/// `total_staked / total_issuance`, minted for an era that lasted `era_duration_millis`.
+pub fn compute_total_payout<Balance>(
+	yearly_inflation: &PiecewiseLinear<'static>,
+	total_staked: Balance,
+	total_issuance: Balance,
+	era_duration_millis: u64,
+) -> Balance
+where
+	Balance: AtLeast32BitUnsigned + Clone,
+{
+	let portion = Perbill::from_rational(era_duration_millis, MILLISECONDS_PER_YEAR);
+	portion *
+		yearly_inflation.calculate_for_fraction_times_denominator(total_staked, total_issuance)
+}
@@ -0,0 +1,1579 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! # DPoS Staking Pallet
+//!
+//! A delegated proof-of-stake pallet. Accounts `bond` funds to become validator candidates and
+//! any account can `vote` for a candidate with its own funds. The total backing of a candidate is
+//! its own bond plus the votes it received.
+//!
+//! Unbonded funds stay locked for `BondingDuration` eras, during which they can still be slashed,
+//! and can then be released with `withdraw_unbonded`. Pending unbonded funds can be bonded again
+//! with `rebond`.
+//!
+//! Each candidate keeps a commission, a share of the rewards of its voters, before the rest is
+//! shared by stake. A candidate changing its commission with `set_commission` has to wait
+//! `CommissionChangeDelay` eras for the change to take effect, so that its voters can react.
+//!
+//! Candidacy can be restricted to the accounts with an identity judged by a registrar, see
+//! [`CandidateIdentity`] and `set_require_identity`.
+//!
+//! The bonding account is the stash. Each stash has a controller account, the stash itself by
+//! default, which is used to set the session keys. [`StashOf`] maps a controller to its stash, so
+//! that the session keys can be managed by a hot controller while the funds stay in a cold stash.
+//!
+//! The pallet is the `SessionManager` of `pallet_session`. Sessions are grouped in eras of
+//! `SessionsPerEra` sessions, and the validator set only changes when a new era starts: when the
+//! first session of a new era is planned, the candidates with the highest backing are elected, up
+//! to the maximum validator count. If fewer than the minimum validator count are available the
+//! current era goes on with the current validator set, and the election is retried at the next
+//! session.
+//!
+//! Offences are slashed from the own bond of the offender for `BondingDuration` eras. The offences
+//! of a validator in the same era only slash the largest fraction reported, once.
+//!
+//! Validators running an offchain worker compute the election and submit it as an unsigned
+//! transaction, so that it does not need to be computed on-chain, see the [`election`] module.
+//!
+//! The stake backing each elected validator is recorded as an [`Exposure`] in the era-indexed
+//! history, which keeps the last `HistoryDepth` eras. When an era ends, the era inflation is minted
+//! into the reward pot, see the [`inflation`] module, and the funds of the pot are set aside for
+//! the validators of the era in equal shares. The share of each validator is claimed with
+//! `payout_stakers`, by any account and for the last `HistoryDepth` eras, which pays it from the
+//! pot to the validator and its voters in proportion to their stake. A candidate has at most
+//! `MaxVotersPerCandidate` voters, each voting at least `MinVote`, which bounds the cost of a
+//! payout. The rewards not claimed in time go back to the pot.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod election;
+pub use election::{ElectionScore, ElectionSolution};
+
+pub mod inflation;
+pub use inflation::{ConvertCurve, EraPayout};
+
+pub mod migrations;
+pub mod weights;
+pub use weights::WeightInfo;
+
+use codec::{Decode, Encode};
+use frame_support::{
+	traits::{
+		Currency, ExistenceRequirement, Get, Imbalance, LockIdentifier, LockableCurrency,
+		OnUnbalanced, UnixTime, WithdrawReasons,
+	},
+	weights::Weight,
+	PalletId,
+};
+use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::{
+	traits::{AccountIdConversion, Convert, SaturatedConversion, Saturating, Zero},
+	Perbill, RuntimeDebug,
+};
+use sp_staking::{
+	offence::{DisableStrategy, OffenceDetails, OnOffenceHandler},
+	EraIndex, SessionIndex,
+};
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
+
+pub(crate) const LOG_TARGET: &str = "runtime::dpos";
+
+const STAKING_ID: LockIdentifier = *b"ddpos   ";
+
+pub type BalanceOf<T> = <T as Config>::CurrencyBalance;
+type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+	<T as frame_system::Config>::AccountId,
+>>::NegativeImbalance;
+
+/// A validator candidate together with the stake backing it.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct CandidateInfo<AccountId, Balance> {
+	/// The candidate account.
+	pub who: AccountId,
+	/// The amount bonded by the candidate itself.
+	pub bond: Balance,
+	/// The own bond plus all the votes received.
+	pub total: Balance,
+	/// The share of the rewards kept by the candidate before sharing them with its voters.
+	pub commission: Perbill,
+	/// The display name of the candidate identity. Only set by [`Pallet::candidates_info`].
+	pub display_name: Option<Vec<u8>>,
+}
+
+/// The stake backing an elected validator, snapshotted at election time.
+#[derive(
+	Encode, Decode, Clone, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug, TypeInfo, Default,
+)]
+pub struct Exposure<AccountId, Balance> {
+	/// The total stake backing the validator.
+	pub total: Balance,
+	/// The validator's own bond.
+	pub own: Balance,
+	/// The voters backing the validator and the amount each of them voted.
+	pub others: Vec<(AccountId, Balance)>,
+}
+
+/// Part of a bond being unbonded, that can be withdrawn once `era` is active.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct UnlockChunk<Balance> {
+	/// The amount to be unlocked.
+	pub value: Balance,
+	/// The era at which the amount can be withdrawn.
+	pub era: EraIndex,
+}
+
+/// An elected validator with its exposure.
+pub type ElectedValidator<T> = (
+	<T as frame_system::Config>::AccountId,
+	Exposure<<T as frame_system::Config>::AccountId, BalanceOf<T>>,
+);
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config:
+		frame_system::Config + frame_system::offchain::SendTransactionTypes<Call<Self>>
+	{
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+
+		/// The staking currency, locked while bonded or voted.
+		type Currency: LockableCurrency<
+			Self::AccountId,
+			Moment = Self::BlockNumber,
+			Balance = Self::CurrencyBalance,
+		>;
+
+		/// Just the `Currency::Balance` type; we have this item to allow us to constrain it to
+		/// `From<u64>`.
+		type CurrencyBalance: sp_runtime::traits::AtLeast32BitUnsigned
+			+ codec::FullCodec
+			+ Copy
+			+ MaybeSerializeDeserialize
+			+ sp_std::fmt::Debug
+			+ Default
+			+ From<u64>
+			+ TypeInfo
+			+ MaxEncodedLen;
+
+		/// Default minimum number of validators to elect.
+		#[pallet::constant]
+		type MinimumValidatorCount: Get<u32>;
+
+		/// Default maximum number of validators to elect.
+		#[pallet::constant]
+		type MaximumValidatorCount: Get<u32>;
+
+		/// Hard limit on the maximum number of validators. This should be the `MaxAuthorities` of
+		/// the consensus pallets, which cannot hold a larger authority set.
+		#[pallet::constant]
+		type MaxValidators: Get<u32>;
+
+		/// Number of sessions per era.
+		#[pallet::constant]
+		type SessionsPerEra: Get<SessionIndex>;
+
+		/// Number of eras kept in the era-indexed history.
+		#[pallet::constant]
+		type HistoryDepth: Get<EraIndex>;
+
+		/// The inflation minted into the reward pot at the end of every era.
+		type EraPayout: EraPayout<BalanceOf<Self>>;
+
+		/// Time used to measure the duration of the eras.
+		type UnixTime: UnixTime;
+
+		/// The staking pallet id, from which the reward pot account is derived.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+
+		/// Handler for the unbalanced reduction when slashing a validator.
+		type Slash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+
+		/// Interface to the session pallet, used to disable validators and to prune the
+		/// historical sessions.
+		type SessionInterface: SessionInterface<Self::AccountId>;
+
+		/// The current consensus slot, used to detect the validators missing their slots.
+		type CurrentSlot: Get<u64>;
+
+		/// Number of consecutive slots a validator can miss before being disabled.
+		#[pallet::constant]
+		type MaxMissedSlots: Get<u32>;
+
+		/// Number of eras unbonded funds stay locked, and slashable, before they can be withdrawn.
+		#[pallet::constant]
+		type BondingDuration: Get<EraIndex>;
+
+		/// Maximum number of unlocking chunks a stash can have pending.
+		#[pallet::constant]
+		type MaxUnlockingChunks: Get<u32>;
+
+		/// Maximum number of accounts voting for a candidate, which bounds the cost of a payout.
+		#[pallet::constant]
+		type MaxVotersPerCandidate: Get<u32>;
+
+		/// The smallest vote an account can have for a candidate, so that dust votes cannot take
+		/// the places of the voters of a candidate.
+		#[pallet::constant]
+		type MinVote: Get<BalanceOf<Self>>;
+
+		/// The origin allowed to change the staking parameters, like the validator count bounds.
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+		/// The priority of the unsigned election solutions.
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+
+		/// The identities of the candidates.
+		type Identity: CandidateIdentity<Self::AccountId>;
+
+		/// Number of eras a commission change waits before taking effect.
+		#[pallet::constant]
+		type CommissionChangeDelay: Get<EraIndex>;
+
+		/// Weight information for the extrinsics of this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	/// The amount bonded by each candidate.
+	#[pallet::storage]
+	#[pallet::getter(fn candidates)]
+	pub type Candidates<T: Config> = CountedStorageMap<_, Twox64Concat, T::AccountId, BalanceOf<T>>;
+
+	/// The funds being unbonded by each stash, ordered by unlock era.
+	#[pallet::storage]
+	#[pallet::getter(fn unlocking)]
+	pub type Unlocking<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, Vec<UnlockChunk<BalanceOf<T>>>, ValueQuery>;
+
+	/// Whether a stash needs an identity judged by a registrar to become a candidate.
+	#[pallet::storage]
+	#[pallet::getter(fn require_identity)]
+	pub type RequireIdentity<T> = StorageValue<_, bool, ValueQuery>;
+
+	/// The controller of each bonded stash.
+	#[pallet::storage]
+	#[pallet::getter(fn bonded)]
+	pub type Bonded<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, T::AccountId>;
+
+	/// The stash controlled by each controller.
+	#[pallet::storage]
+	#[pallet::getter(fn stash_of)]
+	pub type Stashes<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, T::AccountId>;
+
+	/// The commission of each candidate, kept across unbonding.
+	#[pallet::storage]
+	#[pallet::getter(fn commission)]
+	pub type Commission<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, Perbill, ValueQuery>;
+
+	/// The commission changes not in effect yet, with the era from which they apply.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_commission)]
+	pub type PendingCommission<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, (Perbill, EraIndex)>;
+
+	/// The amount voted by a voter (first key) for a candidate (second key).
+	#[pallet::storage]
+	#[pallet::getter(fn votes)]
+	pub type Votes<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::AccountId, Twox64Concat, T::AccountId, BalanceOf<T>>;
+
+	/// The sum of all the votes received by each candidate.
+	#[pallet::storage]
+	#[pallet::getter(fn votes_received)]
+	pub type VotesReceived<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+
+	/// The number of accounts voting for each candidate.
+	#[pallet::storage]
+	#[pallet::getter(fn voter_count)]
+	pub type VoterCount<T: Config> = StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	/// The minimum number of validators needed to change the validator set.
+	#[pallet::storage]
+	#[pallet::getter(fn minimum_validators)]
+	pub type MinimumValidators<T: Config> =
+		StorageValue<_, u32, ValueQuery, T::MinimumValidatorCount>;
+
+	/// The maximum number of validators to elect.
+	#[pallet::storage]
+	#[pallet::getter(fn maximum_validators)]
+	pub type MaximumValidators<T: Config> =
+		StorageValue<_, u32, ValueQuery, T::MaximumValidatorCount>;
+
+	/// The index of the session currently running.
+	#[pallet::storage]
+	#[pallet::getter(fn current_session)]
+	pub type CurrentSession<T> = StorageValue<_, SessionIndex, ValueQuery>;
+
+	/// The latest planned era. `None` until the first election succeeds.
+	#[pallet::storage]
+	#[pallet::getter(fn current_era)]
+	pub type CurrentEra<T> = StorageValue<_, EraIndex>;
+
+	/// The era currently running. `None` until the first elected validator set is active.
+	#[pallet::storage]
+	#[pallet::getter(fn active_era)]
+	pub type ActiveEra<T> = StorageValue<_, EraIndex>;
+
+	/// The time, in milliseconds, at which the active era started. Set at the end of the first
+	/// block of the era, once the timestamp is known.
+	#[pallet::storage]
+	#[pallet::getter(fn active_era_start)]
+	pub type ActiveEraStart<T> = StorageValue<_, u64>;
+
+	/// The session index at which each era started.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_start_session_index)]
+	pub type ErasStartSessionIndex<T> = StorageMap<_, Twox64Concat, EraIndex, SessionIndex>;
+
+	/// The validators elected for each era, in election order.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_validators)]
+	pub type ErasValidators<T: Config> =
+		StorageMap<_, Twox64Concat, EraIndex, Vec<T::AccountId>, ValueQuery>;
+
+	/// The exposure of each validator elected for an era.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_stakers)]
+	pub type ErasStakers<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		Twox64Concat,
+		T::AccountId,
+		Exposure<T::AccountId, BalanceOf<T>>,
+	>;
+
+	/// The best election solution submitted for the next era.
+	#[pallet::storage]
+	#[pallet::getter(fn queued_solution)]
+	pub type QueuedSolution<T: Config> =
+		StorageValue<_, ElectionSolution<T::AccountId, BalanceOf<T>>>;
+
+	/// The total reward set aside for the validators of each era.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_reward)]
+	pub type ErasReward<T: Config> = StorageMap<_, Twox64Concat, EraIndex, BalanceOf<T>>;
+
+	/// The reward of each validator of an era not claimed yet.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_validator_reward)]
+	pub type ErasValidatorReward<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, EraIndex, Twox64Concat, T::AccountId, BalanceOf<T>>;
+
+	/// The commission of each validator of an era, kept until its reward is claimed.
+	#[pallet::storage]
+	#[pallet::getter(fn eras_validator_commission)]
+	pub type ErasValidatorCommission<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		Twox64Concat,
+		T::AccountId,
+		Perbill,
+		ValueQuery,
+	>;
+
+	/// The rewards of the reward pot set aside for the validators and not claimed yet.
+	#[pallet::storage]
+	#[pallet::getter(fn unclaimed_rewards)]
+	pub type UnclaimedRewards<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
+	/// The amount already slashed from each validator for the offences committed in an era.
+	#[pallet::storage]
+	#[pallet::getter(fn slashed_in_era)]
+	pub type SlashedInEra<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		Twox64Concat,
+		T::AccountId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// Indices, in the current validator set, of the validators disabled until the next election.
+	///
+	/// The vector is kept sorted.
+	#[pallet::storage]
+	#[pallet::getter(fn disabled_validators)]
+	pub type DisabledValidators<T> = StorageValue<_, Vec<u32>, ValueQuery>;
+
+	/// Consecutive slots missed by each validator of the current set, by index.
+	#[pallet::storage]
+	#[pallet::getter(fn missed_slots)]
+	pub type MissedSlots<T> = StorageValue<_, Vec<u32>, ValueQuery>;
+
+	/// The slot of the last block imported.
+	#[pallet::storage]
+	pub type LastSlot<T> = StorageValue<_, u64, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// An account bonded funds and is a validator candidate. [candidate, amount]
+		Bonded(T::AccountId, BalanceOf<T>),
+		/// A candidate unbonded funds, withdrawable at the given era. [candidate, amount, era]
+		Unbonded(T::AccountId, BalanceOf<T>, EraIndex),
+		/// Unbonded funds have been withdrawn and unlocked. [stash, amount]
+		Withdrawn(T::AccountId, BalanceOf<T>),
+		/// Pending unbonded funds have been bonded again. [candidate, amount]
+		Rebonded(T::AccountId, BalanceOf<T>),
+		/// An account voted for a candidate. [voter, candidate, amount]
+		Voted(T::AccountId, T::AccountId, BalanceOf<T>),
+		/// An account removed its vote for a candidate. [voter, candidate, amount]
+		Unvoted(T::AccountId, T::AccountId, BalanceOf<T>),
+		/// The minimum number of validators has been changed. [count]
+		MinimumValidatorsSet(u32),
+		/// The maximum number of validators has been changed. [count]
+		MaximumValidatorsSet(u32),
+		/// The identity requirement for candidacy has been changed. [required]
+		IdentityRequirementSet(bool),
+		/// A new validator set has been elected for an era. [era, validators]
+		ValidatorsElected(EraIndex, Vec<T::AccountId>),
+		/// A new era has started. [era]
+		EraStarted(EraIndex),
+		/// An election solution has been queued for an era. [era]
+		SolutionStored(EraIndex),
+		/// A validator or voter has been rewarded. [who, amount]
+		Rewarded(T::AccountId, BalanceOf<T>),
+		/// The inflation for an era has been minted into the reward pot. [era, amount]
+		RewardMinted(EraIndex, BalanceOf<T>),
+		/// The reward for an era has been set aside, to be claimed by its validators. [era, total]
+		EraPaid(EraIndex, BalanceOf<T>),
+		/// A validator has been slashed for an offence. [validator, amount]
+		Slashed(T::AccountId, BalanceOf<T>),
+		/// A validator has been disabled until the next election. [validator]
+		ValidatorDisabled(T::AccountId),
+		/// A stash changed its controller. [stash, controller]
+		ControllerChanged(T::AccountId, T::AccountId),
+		/// A candidate scheduled a commission change from the given era. [candidate, commission,
+		/// era]
+		CommissionChangeScheduled(T::AccountId, Perbill, EraIndex),
+		/// The commission of a candidate has changed. [candidate, commission]
+		CommissionSet(T::AccountId, Perbill),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The amount must be greater than zero.
+		ZeroAmount,
+		/// The free balance is not enough to lock the requested amount.
+		InsufficientBalance,
+		/// The account is not a bonded candidate.
+		NotCandidate,
+		/// The account has not voted for this candidate.
+		NoVote,
+		/// The validator count would make the minimum greater than the maximum, or be zero.
+		InvalidValidatorCount,
+		/// The account is already a stash or a controller of another stash.
+		AlreadyPaired,
+		/// The validator count is above the `MaxValidators` hard limit.
+		TooManyValidators,
+		/// The stash has too many unlocking chunks pending. Withdraw the unlocked ones first.
+		NoMoreChunks,
+		/// There are no unbonded funds to rebond.
+		NoUnlockChunk,
+		/// The candidate already has `MaxVotersPerCandidate` voters.
+		TooManyVoters,
+		/// The vote for the candidate would be below `MinVote`.
+		VoteTooSmall,
+		/// The validator has no reward to claim for the era, or has already claimed it.
+		NothingToClaim,
+		/// The election solution is not for the next era.
+		WrongEra,
+		/// The election solution does not elect as many validators as the on-chain election.
+		WrongSolutionSize,
+		/// The election solution does not match the bonds and votes.
+		InvalidSolution,
+		/// The election solution does not score better than the queued one.
+		WeakSolution,
+		/// The election solution leaves out a candidate backed more than one of its winners.
+		SuboptimalSolution,
+		/// The stash has no identity judged by a registrar, which is required to be a candidate.
+		NoIdentity,
+	}
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		/// Candidates bonded at genesis, as `(stash, controller, amount)`.
+		pub candidates: Vec<(T::AccountId, T::AccountId, BalanceOf<T>)>,
+		/// Votes cast at genesis, as `(voter, candidate, amount)`.
+		pub votes: Vec<(T::AccountId, T::AccountId, BalanceOf<T>)>,
+		/// The minimum number of validators needed to change the validator set.
+		pub minimum_validators: u32,
+		/// The maximum number of validators to elect.
+		pub maximum_validators: u32,
+		/// Whether candidates need a judged identity. Not enforced for the genesis candidates.
+		pub require_identity: bool,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self {
+				candidates: Default::default(),
+				votes: Default::default(),
+				minimum_validators: T::MinimumValidatorCount::get(),
+				maximum_validators: T::MaximumValidatorCount::get(),
+				require_identity: false,
+			}
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			assert!(
+				self.minimum_validators > 0 && self.minimum_validators <= self.maximum_validators,
+				"Invalid validator count bounds in genesis."
+			);
+			assert!(
+				self.maximum_validators <= T::MaxValidators::get(),
+				"Genesis maximum validators is above `MaxValidators`."
+			);
+			MinimumValidators::<T>::put(self.minimum_validators);
+			MaximumValidators::<T>::put(self.maximum_validators);
+
+			// The reward pot keeps the existential deposit, so it is never reaped when paid out.
+			let pot = Pallet::<T>::reward_pot();
+			let minimum = T::Currency::minimum_balance();
+			if T::Currency::free_balance(&pot) < minimum {
+				T::Currency::make_free_balance_be(&pot, minimum);
+			}
+
+			for (stash, controller, value) in &self.candidates {
+				Pallet::<T>::bond(frame_system::RawOrigin::Signed(stash.clone()).into(), *value)
+					.expect("Genesis candidate can bond; qed");
+				if controller != stash {
+					Pallet::<T>::set_controller(
+						frame_system::RawOrigin::Signed(stash.clone()).into(),
+						controller.clone(),
+					)
+					.expect("Genesis controller is not paired yet; qed");
+				}
+			}
+
+			for (voter, candidate, value) in &self.votes {
+				Pallet::<T>::vote(
+					frame_system::RawOrigin::Signed(voter.clone()).into(),
+					candidate.clone(),
+					*value,
+				)
+				.expect("Genesis voter can vote for a genesis candidate; qed");
+			}
+
+			RequireIdentity::<T>::put(self.require_identity);
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+			Self::note_slot(T::CurrentSlot::get());
+			// Also accounts for `on_finalize`.
+			T::DbWeight::get().reads_writes(6, 4)
+		}
+
+		fn on_finalize(_n: BlockNumberFor<T>) {
+			if Self::active_era().is_some() && ActiveEraStart::<T>::get().is_none() {
+				let now = T::UnixTime::now().as_millis().saturated_into::<u64>();
+				ActiveEraStart::<T>::put(now);
+			}
+		}
+
+		fn offchain_worker(_n: BlockNumberFor<T>) {
+			Self::offchain_election();
+		}
+
+		fn integrity_test() {
+			let (min, max) = (T::MinimumValidatorCount::get(), T::MaximumValidatorCount::get());
+			assert!(min > 0, "`MinimumValidatorCount` must be greater than zero.");
+			assert!(min <= max, "`MinimumValidatorCount` is above `MaximumValidatorCount`.");
+			assert!(
+				max <= T::MaxValidators::get(),
+				"`MaximumValidatorCount` is above `MaxValidators`."
+			);
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T> {
+		type Call = Call<T>;
+
+		fn validate_unsigned(source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			if let Call::submit_election_solution { solution } = call {
+				Self::validate_solution(source, solution)
+			} else {
+				InvalidTransaction::Call.into()
+			}
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Bond `value` to become a validator candidate, or add `value` to an existing bond.
+		///
+		/// The origin is the stash. On the first bond the stash is its own controller.
+		#[pallet::weight(T::WeightInfo::bond())]
+		pub fn bond(
+			origin: OriginFor<T>,
+			#[pallet::compact] value: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!value.is_zero(), Error::<T>::ZeroAmount);
+			Self::ensure_can_lock(&who, value)?;
+
+			Self::add_bond(&who, value)?;
+			Self::update_lock(&who);
+
+			Self::deposit_event(Event::Bonded(who, value));
+			Ok(())
+		}
+
+		/// Schedule up to `value` of the bond to be unlocked after `BondingDuration` eras.
+		///
+		/// The funds stay locked, and slashable, until they are withdrawn with
+		/// `withdraw_unbonded`. A candidate unbonding its whole bond leaves the candidate pool:
+		/// the votes it received are kept but do not count until it bonds again.
+		#[pallet::weight(T::WeightInfo::unbond())]
+		pub fn unbond(
+			origin: OriginFor<T>,
+			#[pallet::compact] value: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let bond = Candidates::<T>::get(&who).ok_or(Error::<T>::NotCandidate)?;
+			let value = value.min(bond);
+			ensure!(!value.is_zero(), Error::<T>::ZeroAmount);
+
+			let era =
+				Self::active_era().unwrap_or_default().saturating_add(T::BondingDuration::get());
+			let mut unlocking = Unlocking::<T>::get(&who);
+			match unlocking.last_mut() {
+				Some(chunk) if chunk.era == era => chunk.value = chunk.value.saturating_add(value),
+				_ => {
+					ensure!(
+						unlocking.len() < T::MaxUnlockingChunks::get() as usize,
+						Error::<T>::NoMoreChunks
+					);
+					unlocking.push(UnlockChunk { value, era });
+				},
+			}
+			Unlocking::<T>::insert(&who, unlocking);
+
+			let remaining = bond.saturating_sub(value);
+			if remaining.is_zero() {
+				Self::remove_candidate(&who);
+			} else {
+				Candidates::<T>::insert(&who, remaining);
+			}
+			Self::update_queued_solution(&who, None);
+
+			Self::deposit_event(Event::Unbonded(who, value, era));
+			Ok(())
+		}
+
+		/// Unlock the unbonded funds whose bonding duration has passed.
+		#[pallet::weight(T::WeightInfo::withdraw_unbonded())]
+		pub fn withdraw_unbonded(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let active_era = Self::active_era().unwrap_or_default();
+
+			let mut withdrawn = BalanceOf::<T>::zero();
+			Unlocking::<T>::mutate_exists(&who, |maybe_unlocking| {
+				let unlocking = maybe_unlocking.take().unwrap_or_default();
+				let pending = unlocking
+					.into_iter()
+					.filter(|chunk| {
+						if chunk.era > active_era {
+							return true
+						}
+						withdrawn = withdrawn.saturating_add(chunk.value);
+						false
+					})
+					.collect::<Vec<_>>();
+				*maybe_unlocking = Some(pending).filter(|pending| !pending.is_empty());
+			});
+			Self::update_lock(&who);
+
+			if !withdrawn.is_zero() {
+				Self::deposit_event(Event::Withdrawn(who, withdrawn));
+			}
+			Ok(())
+		}
+
+		/// Bond again up to `value` of the funds being unbonded, latest unbonded first.
+		#[pallet::weight(T::WeightInfo::rebond())]
+		pub fn rebond(
+			origin: OriginFor<T>,
+			#[pallet::compact] value: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!value.is_zero(), Error::<T>::ZeroAmount);
+			let mut unlocking = Unlocking::<T>::get(&who);
+			ensure!(!unlocking.is_empty(), Error::<T>::NoUnlockChunk);
+
+			let mut rebonded = BalanceOf::<T>::zero();
+			while let Some(chunk) = unlocking.last_mut() {
+				let take = chunk.value.min(value.saturating_sub(rebonded));
+				chunk.value = chunk.value.saturating_sub(take);
+				rebonded = rebonded.saturating_add(take);
+				if !chunk.value.is_zero() {
+					break
+				}
+				unlocking.pop();
+			}
+
+			Self::add_bond(&who, rebonded)?;
+			if unlocking.is_empty() {
+				Unlocking::<T>::remove(&who);
+			} else {
+				Unlocking::<T>::insert(&who, unlocking);
+			}
+
+			Self::deposit_event(Event::Rebonded(who, rebonded));
+			Ok(())
+		}
+
+		/// Set the controller of the stash. The origin must be a bonded stash.
+		///
+		/// The controller can set the session keys of the stash.
+		#[pallet::weight(T::WeightInfo::set_controller())]
+		pub fn set_controller(origin: OriginFor<T>, controller: T::AccountId) -> DispatchResult {
+			let stash = ensure_signed(origin)?;
+			let old_controller = Bonded::<T>::get(&stash).ok_or(Error::<T>::NotCandidate)?;
+			if old_controller == controller {
+				return Ok(())
+			}
+			ensure!(
+				!Stashes::<T>::contains_key(&controller) &&
+					(controller == stash || !Bonded::<T>::contains_key(&controller)),
+				Error::<T>::AlreadyPaired
+			);
+
+			Stashes::<T>::remove(&old_controller);
+			Stashes::<T>::insert(&controller, &stash);
+			Bonded::<T>::insert(&stash, &controller);
+
+			Self::deposit_event(Event::ControllerChanged(stash, controller));
+			Ok(())
+		}
+
+		/// Change the commission of the candidate, `CommissionChangeDelay` eras after the active
+		/// one. The origin must be a bonded stash.
+		///
+		/// A pending change is replaced, with a new delay.
+		#[pallet::weight(T::WeightInfo::set_commission())]
+		pub fn set_commission(origin: OriginFor<T>, commission: Perbill) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Candidates::<T>::contains_key(&who), Error::<T>::NotCandidate);
+
+			let era = Self::active_era()
+				.unwrap_or_default()
+				.saturating_add(T::CommissionChangeDelay::get());
+			PendingCommission::<T>::insert(&who, (commission, era));
+
+			Self::deposit_event(Event::CommissionChangeScheduled(who, commission, era));
+			Ok(())
+		}
+
+		/// Vote for `candidate` with `value`, adding to any previous vote for it.
+		///
+		/// The vote must add up to at least `MinVote`, and a new voter is rejected once the
+		/// candidate has `MaxVotersPerCandidate` voters.
+		#[pallet::weight(T::WeightInfo::vote())]
+		pub fn vote(
+			origin: OriginFor<T>,
+			candidate: T::AccountId,
+			#[pallet::compact] value: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!value.is_zero(), Error::<T>::ZeroAmount);
+			ensure!(Candidates::<T>::contains_key(&candidate), Error::<T>::NotCandidate);
+			Self::ensure_can_lock(&who, value)?;
+			let previous = Votes::<T>::get(&who, &candidate);
+			ensure!(
+				previous.unwrap_or_default().saturating_add(value) >= T::MinVote::get(),
+				Error::<T>::VoteTooSmall
+			);
+			if previous.is_none() {
+				ensure!(
+					VoterCount::<T>::get(&candidate) < T::MaxVotersPerCandidate::get(),
+					Error::<T>::TooManyVoters
+				);
+				VoterCount::<T>::mutate(&candidate, |count| *count += 1);
+			}
+
+			Votes::<T>::mutate(&who, &candidate, |vote| {
+				*vote = Some(vote.unwrap_or_default().saturating_add(value))
+			});
+			VotesReceived::<T>::mutate(&candidate, |total| *total = total.saturating_add(value));
+			Self::update_queued_solution(&candidate, Some(&who));
+			Self::update_lock(&who);
+
+			Self::deposit_event(Event::Voted(who, candidate, value));
+			Ok(())
+		}
+
+		/// Remove the whole vote for `candidate`.
+		#[pallet::weight(T::WeightInfo::unvote())]
+		pub fn unvote(origin: OriginFor<T>, candidate: T::AccountId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let value = Votes::<T>::take(&who, &candidate).ok_or(Error::<T>::NoVote)?;
+
+			VotesReceived::<T>::mutate_exists(&candidate, |total| {
+				*total = total.map(|t| t.saturating_sub(value)).filter(|t| !t.is_zero())
+			});
+			VoterCount::<T>::mutate_exists(&candidate, |count| {
+				*count = count.map(|c| c.saturating_sub(1)).filter(|c| *c > 0)
+			});
+			Self::update_queued_solution(&candidate, Some(&who));
+			Self::update_lock(&who);
+
+			Self::deposit_event(Event::Unvoted(who, candidate, value));
+			Ok(())
+		}
+
+		/// Pay the reward of `validator_stash` for `era` to the validator and its voters. Any
+		/// signed origin can claim it, for the last `HistoryDepth` eras.
+		#[pallet::weight(T::WeightInfo::payout_stakers(T::MaxVotersPerCandidate::get()))]
+		pub fn payout_stakers(
+			origin: OriginFor<T>,
+			validator_stash: T::AccountId,
+			era: EraIndex,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			let reward = ErasValidatorReward::<T>::take(era, &validator_stash)
+				.ok_or(Error::<T>::NothingToClaim)?;
+			let commission = ErasValidatorCommission::<T>::take(era, &validator_stash);
+
+			let exposure = ErasStakers::<T>::get(era, &validator_stash).unwrap_or_default();
+			Self::pay_stakers(validator_stash, exposure, reward, commission);
+			Ok(())
+		}
+
+		/// Queue an election solution for the next era, computed by an offchain worker.
+		///
+		/// The solution must be valid and score better than the queued one.
+		#[pallet::weight(T::WeightInfo::submit_election_solution(
+			Candidates::<T>::count(),
+			solution.vote_count(),
+		))]
+		pub fn submit_election_solution(
+			origin: OriginFor<T>,
+			solution: ElectionSolution<T::AccountId, BalanceOf<T>>,
+		) -> DispatchResult {
+			ensure_none(origin)?;
+			Self::check_solution(&solution)?;
+
+			let era = solution.era;
+			QueuedSolution::<T>::put(solution);
+
+			Self::deposit_event(Event::SolutionStored(era));
+			Ok(())
+		}
+
+		/// Set the minimum number of validators needed to change the validator set.
+		///
+		/// The dispatch origin must be `AdminOrigin`.
+		#[pallet::weight(T::WeightInfo::set_minimum_validators())]
+		pub fn set_minimum_validators(
+			origin: OriginFor<T>,
+			#[pallet::compact] count: u32,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(
+				count > 0 && count <= MaximumValidators::<T>::get(),
+				Error::<T>::InvalidValidatorCount
+			);
+
+			MinimumValidators::<T>::put(count);
+
+			Self::deposit_event(Event::MinimumValidatorsSet(count));
+			Ok(())
+		}
+
+		/// Set the maximum number of validators to elect, at most `MaxValidators`.
+		///
+		/// The dispatch origin must be `AdminOrigin`.
+		#[pallet::weight(T::WeightInfo::set_maximum_validators())]
+		pub fn set_maximum_validators(
+			origin: OriginFor<T>,
+			#[pallet::compact] count: u32,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(count <= T::MaxValidators::get(), Error::<T>::TooManyValidators);
+			ensure!(count >= MinimumValidators::<T>::get(), Error::<T>::InvalidValidatorCount);
+
+			MaximumValidators::<T>::put(count);
+
+			Self::deposit_event(Event::MaximumValidatorsSet(count));
+			Ok(())
+		}
+
+		/// Set whether a stash needs an identity judged by a registrar to become a candidate.
+		///
+		/// The current candidates are not affected. The dispatch origin must be `AdminOrigin`.
+		#[pallet::weight(T::WeightInfo::set_require_identity())]
+		pub fn set_require_identity(origin: OriginFor<T>, required: bool) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			RequireIdentity::<T>::put(required);
+
+			Self::deposit_event(Event::IdentityRequirementSet(required));
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The amount locked by `who`: its own bond plus all the votes it cast.
+	pub fn locked(who: &T::AccountId) -> BalanceOf<T> {
+		let bonded = Candidates::<T>::get(who).unwrap_or_default();
+		let unlocking = Self::unbonding(who);
+		Votes::<T>::iter_prefix_values(who)
+			.fold(bonded.saturating_add(unlocking), |acc, v| acc.saturating_add(v))
+	}
+
+	/// The total amount being unbonded by `who`.
+	pub fn unbonding(who: &T::AccountId) -> BalanceOf<T> {
+		Unlocking::<T>::get(who)
+			.into_iter()
+			.fold(Zero::zero(), |acc: BalanceOf<T>, chunk| acc.saturating_add(chunk.value))
+	}
+
+	/// The votes cast by `voter`, as `(candidate, amount)` pairs.
+	pub fn votes_of(voter: &T::AccountId) -> Vec<(T::AccountId, BalanceOf<T>)> {
+		Votes::<T>::iter_prefix(voter).collect()
+	}
+
+	/// All the candidates with their backing, ordered by total backing, highest first.
+	///
+	/// Ties are broken by account id so that the ranking is deterministic.
+	pub fn ranking() -> Vec<CandidateInfo<T::AccountId, BalanceOf<T>>> {
+		let mut ranking = Candidates::<T>::iter()
+			.map(|(who, bond)| {
+				let total = bond.saturating_add(VotesReceived::<T>::get(&who));
+				let commission = Commission::<T>::get(&who);
+				CandidateInfo { who, bond, total, commission, display_name: None }
+			})
+			.collect::<Vec<_>>();
+		ranking.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.who.cmp(&b.who)));
+		ranking
+	}
+
+	/// The own bonds of all the candidates plus all the votes, elected or not.
+	pub fn total_bonded() -> BalanceOf<T> {
+		Candidates::<T>::iter_values()
+			.chain(VotesReceived::<T>::iter_values())
+			.fold(Zero::zero(), |sum: BalanceOf<T>, value| sum.saturating_add(value))
+	}
+
+	/// The [`ranking`](Self::ranking) with the display name of each candidate.
+	pub fn candidates_info() -> Vec<CandidateInfo<T::AccountId, BalanceOf<T>>> {
+		Self::ranking()
+			.into_iter()
+			.map(|info| CandidateInfo {
+				display_name: T::Identity::display_name(&info.who),
+				..info
+			})
+			.collect()
+	}
+
+	/// The validators of the latest planned era.
+	pub fn next_validators() -> Vec<T::AccountId> {
+		Self::current_era().map(ErasValidators::<T>::get).unwrap_or_default()
+	}
+
+	/// Elect the best ranked candidates, or `None` if there are not enough of them.
+	///
+	/// The voters of each exposure are sorted by account.
+	pub fn elect() -> Option<Vec<ElectedValidator<T>>> {
+		let mut ranking = Self::ranking();
+		ranking.truncate(Self::maximum_validators() as usize);
+
+		if ranking.is_empty() || ranking.len() < Self::minimum_validators() as usize {
+			log::info!(
+				target: LOG_TARGET,
+				"not enough candidates ({}) to elect a new validator set",
+				ranking.len()
+			);
+			return None
+		}
+
+		let mut exposures = ranking
+			.iter()
+			.map(|c| (c.who.clone(), Exposure { total: c.total, own: c.bond, others: Vec::new() }))
+			.collect::<BTreeMap<_, _>>();
+		for (voter, candidate, value) in Votes::<T>::iter() {
+			if let Some(exposure) = exposures.get_mut(&candidate) {
+				exposure.others.push((voter, value));
+			}
+		}
+		for exposure in exposures.values_mut() {
+			exposure.others.sort_by(|a, b| a.0.cmp(&b.0));
+		}
+
+		Some(
+			ranking
+				.into_iter()
+				.filter_map(|c| exposures.remove(&c.who).map(|exposure| (c.who, exposure)))
+				.collect(),
+		)
+	}
+
+	/// The validators of the active era. Empty until the first elected validator set is active.
+	pub fn current_validators() -> Vec<T::AccountId> {
+		Self::active_era().map(ErasValidators::<T>::get).unwrap_or_default()
+	}
+
+	/// The validators elected for `era`, with their exposure.
+	fn era_exposures(era: EraIndex) -> Vec<ElectedValidator<T>> {
+		ErasValidators::<T>::get(era)
+			.into_iter()
+			.map(|who| {
+				let exposure = ErasStakers::<T>::get(era, &who).unwrap_or_default();
+				(who, exposure)
+			})
+			.collect()
+	}
+
+	/// Plan a new era starting at `start_session`, if enough candidates can be elected.
+	fn try_plan_era(start_session: SessionIndex) -> Option<Vec<T::AccountId>> {
+		let elected = Self::elect_next_era()?;
+		let era = Self::next_era();
+		let validators = elected.iter().map(|(who, _)| who.clone()).collect::<Vec<_>>();
+		log::info!(target: LOG_TARGET, "elected for era {}: {:?}", era, validators);
+
+		for (who, exposure) in elected {
+			ErasStakers::<T>::insert(era, who, exposure);
+		}
+		ErasValidators::<T>::insert(era, &validators);
+		ErasStartSessionIndex::<T>::insert(era, start_session);
+		CurrentEra::<T>::put(era);
+
+		Self::deposit_event(Event::ValidatorsElected(era, validators.clone()));
+		Some(validators)
+	}
+
+	/// Make `era` the active era, apply the commission changes due, and prune the history older
+	/// than `HistoryDepth` eras and the offence history older than `BondingDuration` eras.
+	fn start_era(era: EraIndex) {
+		ActiveEra::<T>::put(era);
+		ActiveEraStart::<T>::kill();
+		DisabledValidators::<T>::kill();
+		MissedSlots::<T>::kill();
+
+		// Offences can only be reported for the sessions of the last `BondingDuration` eras.
+		if let Some(first_slashable) = era.checked_sub(T::BondingDuration::get()) {
+			if let Some(start) = Self::eras_start_session_index(first_slashable) {
+				T::SessionInterface::prune_historical_up_to(start);
+			}
+			if let Some(old_era) = first_slashable.checked_sub(1) {
+				#[allow(deprecated)]
+				SlashedInEra::<T>::remove_prefix(old_era, None);
+			}
+		}
+
+		let due = PendingCommission::<T>::iter()
+			.filter(|(_, (_, from))| *from <= era)
+			.collect::<Vec<_>>();
+		for (who, (commission, _)) in due {
+			PendingCommission::<T>::remove(&who);
+			Commission::<T>::insert(&who, commission);
+			Self::deposit_event(Event::CommissionSet(who, commission));
+		}
+
+		if let Some(old_era) = era.checked_sub(T::HistoryDepth::get()) {
+			ErasValidators::<T>::remove(old_era);
+			#[allow(deprecated)]
+			ErasStakers::<T>::remove_prefix(old_era, None);
+			ErasStartSessionIndex::<T>::remove(old_era);
+			ErasReward::<T>::remove(old_era);
+			#[allow(deprecated)]
+			ErasValidatorCommission::<T>::remove_prefix(old_era, None);
+			// The rewards never claimed go back to the funds available in the reward pot.
+			let expired = ErasValidatorReward::<T>::drain_prefix(old_era)
+				.fold(BalanceOf::<T>::zero(), |sum, (_, reward)| sum.saturating_add(reward));
+			UnclaimedRewards::<T>::mutate(|unclaimed| {
+				*unclaimed = unclaimed.saturating_sub(expired)
+			});
+		}
+
+		Self::deposit_event(Event::EraStarted(era));
+	}
+
+	/// The account holding the rewards until they are paid out.
+	pub fn reward_pot() -> T::AccountId {
+		T::PalletId::get().into_account_truncating()
+	}
+
+	/// Mint the inflation for `era` into the reward pot, then set the funds available in the pot
+	/// aside for the validators of `era`, in equal shares claimed with `payout_stakers`. The
+	/// commissions of the validators are kept with their shares.
+	///
+	/// What cannot be set aside, like the rounding remainders, stays available for the next era.
+	fn reward_era(era: EraIndex) {
+		let validators = ErasValidators::<T>::get(era);
+		if validators.is_empty() {
+			return
+		}
+
+		Self::mint_era_payout(era, Self::total_bonded());
+
+		let count = BalanceOf::<T>::from(validators.len() as u64);
+		let per_validator = Self::available_rewards() / count;
+		for validator in &validators {
+			ErasValidatorReward::<T>::insert(era, validator, per_validator);
+			ErasValidatorCommission::<T>::insert(era, validator, Commission::<T>::get(validator));
+		}
+
+		let total = per_validator.saturating_mul(count);
+		UnclaimedRewards::<T>::mutate(|unclaimed| *unclaimed = unclaimed.saturating_add(total));
+		log::debug!(target: LOG_TARGET, "set aside {:?} for era {}", total, era);
+		ErasReward::<T>::insert(era, total);
+		Self::deposit_event(Event::EraPaid(era, total));
+	}
+
+	/// The funds of the reward pot, above its existential deposit, not set aside for the
+	/// validators of an era yet.
+	fn available_rewards() -> BalanceOf<T> {
+		T::Currency::free_balance(&Self::reward_pot())
+			.saturating_sub(T::Currency::minimum_balance())
+			.saturating_sub(UnclaimedRewards::<T>::get())
+	}
+
+	/// Pay `reward` from the reward pot to `validator` and its voters. The validator keeps its
+	/// `commission` of the reward, and the rest is shared by stake.
+	///
+	/// What is not paid out, like the rounding remainders, becomes available for the next eras.
+	fn pay_stakers(
+		validator: T::AccountId,
+		exposure: Exposure<T::AccountId, BalanceOf<T>>,
+		reward: BalanceOf<T>,
+		commission: Perbill,
+	) {
+		UnclaimedRewards::<T>::mutate(|unclaimed| *unclaimed = unclaimed.saturating_sub(reward));
+		if exposure.total.is_zero() {
+			return
+		}
+
+		let pot = Self::reward_pot();
+		let commission = commission.mul_floor(reward);
+		let to_stakers = reward.saturating_sub(commission);
+		let stakers = sp_std::iter::once((validator, exposure.own, commission))
+			.chain(exposure.others.into_iter().map(|(who, stake)| (who, stake, Zero::zero())));
+		for (who, stake, commission) in stakers {
+			let amount = Perbill::from_rational(stake, exposure.total)
+				.mul_floor(to_stakers)
+				.saturating_add(commission);
+			if amount.is_zero() ||
+				T::Currency::transfer(&pot, &who, amount, ExistenceRequirement::KeepAlive)
+					.is_err()
+			{
+				continue
+			}
+
+			Self::deposit_event(Event::Rewarded(who, amount));
+		}
+	}
+
+	/// Mint the inflation for the active era `era`, at the staking ratio of `total_staked` over
+	/// the total issuance, into the reward pot.
+	fn mint_era_payout(era: EraIndex, total_staked: BalanceOf<T>) {
+		let now = T::UnixTime::now().as_millis().saturated_into::<u64>();
+		let duration = Self::active_era_start().map_or(0, |start| now.saturating_sub(start));
+		let payout =
+			T::EraPayout::era_payout(total_staked, T::Currency::total_issuance(), duration);
+		if payout.is_zero() {
+			return
+		}
+
+		// Dropping the imbalance increases the total issuance.
+		drop(T::Currency::deposit_creating(&Self::reward_pot(), payout));
+		log::debug!(target: LOG_TARGET, "minted {:?} for era {} ({} ms)", payout, era, duration);
+		Self::deposit_event(Event::RewardMinted(era, payout));
+	}
+
+	/// The era active in `session`, if it is one of the last `BondingDuration` eras.
+	fn slashable_era(session: SessionIndex) -> Option<EraIndex> {
+		let active = Self::active_era()?;
+		(active.saturating_sub(T::BondingDuration::get())..=active)
+			.rev()
+			.find(|era| Self::eras_start_session_index(era).map_or(false, |start| start <= session))
+	}
+
+	/// Slash up to `value` from the bond of `stash`, returning the amount actually slashed.
+	///
+	/// The active bond is slashed first, then the funds being unbonded, latest unbonded first.
+	fn slash_bond(stash: &T::AccountId, value: BalanceOf<T>) -> BalanceOf<T> {
+		let bond = Candidates::<T>::get(stash).unwrap_or_default();
+		let value = value.min(bond.saturating_add(Self::unbonding(stash)));
+		if value.is_zero() {
+			return value
+		}
+
+		let (imbalance, _) = T::Currency::slash(stash, value);
+		let slashed = imbalance.peek();
+		T::Slash::on_unbalanced(imbalance);
+
+		let from_bond = slashed.min(bond);
+		let remaining = bond.saturating_sub(from_bond);
+		if remaining.is_zero() {
+			Self::remove_candidate(stash);
+		} else {
+			Candidates::<T>::insert(stash, remaining);
+		}
+		Self::update_queued_solution(stash, None);
+
+		let mut left = slashed.saturating_sub(from_bond);
+		if !left.is_zero() {
+			Unlocking::<T>::mutate_exists(stash, |maybe_unlocking| {
+				let mut unlocking = maybe_unlocking.take().unwrap_or_default();
+				for chunk in unlocking.iter_mut().rev() {
+					let take = chunk.value.min(left);
+					chunk.value = chunk.value.saturating_sub(take);
+					left = left.saturating_sub(take);
+				}
+				unlocking.retain(|chunk| !chunk.value.is_zero());
+				*maybe_unlocking = Some(unlocking).filter(|unlocking| !unlocking.is_empty());
+			});
+		}
+
+		Self::update_lock(stash);
+		slashed
+	}
+
+	/// Add `value` to the bond of `stash`, making it a candidate paired with itself if needed.
+	///
+	/// A stash becoming a candidate needs a judged identity if `RequireIdentity` is set.
+	fn add_bond(
+		stash: &T::AccountId,
+		value: BalanceOf<T>,
+	) -> frame_support::dispatch::DispatchResult {
+		if Self::require_identity() && !Candidates::<T>::contains_key(stash) {
+			frame_support::ensure!(T::Identity::has_judged_identity(stash), Error::<T>::NoIdentity);
+		}
+		if !Bonded::<T>::contains_key(stash) {
+			frame_support::ensure!(!Stashes::<T>::contains_key(stash), Error::<T>::AlreadyPaired);
+			Bonded::<T>::insert(stash, stash);
+			Stashes::<T>::insert(stash, stash);
+		}
+
+		let bond = Candidates::<T>::get(stash).unwrap_or_default().saturating_add(value);
+		Candidates::<T>::insert(stash, bond);
+		Self::update_queued_solution(stash, None);
+		Ok(())
+	}
+
+	/// Remove `stash` from the candidate pool, together with its controller.
+	fn remove_candidate(stash: &T::AccountId) {
+		Candidates::<T>::remove(stash);
+		if let Some(controller) = Bonded::<T>::take(stash) {
+			Stashes::<T>::remove(controller);
+		}
+	}
+
+	/// Disable the validator at `index` in the current set until the next election.
+	///
+	/// The last enabled validator is never disabled, so that blocks can still be produced.
+	pub fn disable_validator(index: u32) -> bool {
+		let validators = T::SessionInterface::validators();
+		let who = match validators.get(index as usize) {
+			Some(who) => who.clone(),
+			None => return false,
+		};
+
+		let mut disabled = DisabledValidators::<T>::get();
+		let position = match disabled.binary_search(&index) {
+			Ok(_) => return false,
+			Err(position) => position,
+		};
+		if disabled.len() + 1 >= validators.len() {
+			log::warn!(target: LOG_TARGET, "not disabling {:?}: last enabled validator", who);
+			return false
+		}
+
+		disabled.insert(position, index);
+		DisabledValidators::<T>::put(disabled);
+		T::SessionInterface::disable_validator(index);
+
+		log::warn!(target: LOG_TARGET, "disabled validator {:?}", who);
+		Self::deposit_event(Event::ValidatorDisabled(who));
+		true
+	}
+
+	/// Count the slots skipped since the last block against their expected authors, and disable
+	/// the validators that missed too many consecutive slots.
+	///
+	/// Slots are assigned round-robin over the current validator set, as Aura does.
+	fn note_slot(slot: u64) {
+		let last = LastSlot::<T>::mutate(|last| sp_std::mem::replace(last, slot));
+		let validators = T::SessionInterface::validators().len() as u64;
+		if last == 0 || slot <= last || validators == 0 {
+			return
+		}
+
+		let mut missed = MissedSlots::<T>::get();
+		missed.resize(validators as usize, 0);
+
+		let skipped = slot - last - 1;
+		for offset in 0..skipped.min(validators) {
+			let index = ((last + 1 + offset) % validators) as usize;
+			let count = (skipped - 1 - offset) / validators + 1;
+			missed[index] = missed[index].saturating_add(count as u32);
+		}
+		missed[(slot % validators) as usize] = 0;
+
+		let max_missed = T::MaxMissedSlots::get();
+		let offline = missed
+			.iter()
+			.enumerate()
+			.filter(|(_, count)| **count >= max_missed)
+			.map(|(index, _)| index as u32)
+			.collect::<Vec<_>>();
+		MissedSlots::<T>::put(missed);
+
+		for index in offline {
+			Self::disable_validator(index);
+		}
+	}
+
+	/// Ensure `who` can lock `value` more for staking.
+	///
+	/// Only the staking lock is counted against the free balance: the other locks, like vesting,
+	/// overlap with it, so locked funds can still be bonded and voted.
+	fn ensure_can_lock(
+		who: &T::AccountId,
+		value: BalanceOf<T>,
+	) -> frame_support::dispatch::DispatchResult {
+		let required = Self::locked(who).saturating_add(value);
+		frame_support::ensure!(
+			T::Currency::free_balance(who) >= required,
+			Error::<T>::InsufficientBalance
+		);
+		Ok(())
+	}
+
+	fn update_lock(who: &T::AccountId) {
+		let locked = Self::locked(who);
+		if locked.is_zero() {
+			T::Currency::remove_lock(STAKING_ID, who);
+		} else {
+			T::Currency::set_lock(STAKING_ID, who, locked, WithdrawReasons::all());
+		}
+	}
+}
+
+impl<T: Config> pallet_session::SessionManager<T::AccountId> for Pallet<T> {
+	fn new_session(new_index: SessionIndex) -> Option<Vec<T::AccountId>> {
+		log::debug!(target: LOG_TARGET, "planning session {}", new_index);
+
+		if let Some(era) = Self::current_era() {
+			let start = Self::eras_start_session_index(era).unwrap_or_default();
+			if new_index.saturating_sub(start) < T::SessionsPerEra::get() {
+				return None
+			}
+		}
+		Self::try_plan_era(new_index)
+	}
+
+	fn end_session(end_index: SessionIndex) {
+		if let Some(era) = Self::active_era() {
+			let next_start = Self::eras_start_session_index(era.saturating_add(1));
+			if next_start == Some(end_index.saturating_add(1)) {
+				Self::reward_era(era);
+			}
+		}
+	}
+
+	fn start_session(start_index: SessionIndex) {
+		CurrentSession::<T>::put(start_index);
+		let next_era = Self::active_era().map_or(0, |era| era.saturating_add(1));
+		if Self::eras_start_session_index(next_era) == Some(start_index) {
+			Self::start_era(next_era);
+		}
+	}
+}
+
+impl<T: Config>
+	pallet_session::historical::SessionManager<T::AccountId, Exposure<T::AccountId, BalanceOf<T>>>
+	for Pallet<T>
+{
+	fn new_session(new_index: SessionIndex) -> Option<Vec<ElectedValidator<T>>> {
+		<Self as pallet_session::SessionManager<_>>::new_session(new_index)?;
+		Self::current_era().map(Self::era_exposures)
+	}
+
+	fn end_session(end_index: SessionIndex) {
+		<Self as pallet_session::SessionManager<_>>::end_session(end_index)
+	}
+
+	fn start_session(start_index: SessionIndex) {
+		<Self as pallet_session::SessionManager<_>>::start_session(start_index)
+	}
+}
+
+/// A `Convert` implementation that finds the stash of the given controller account.
+pub struct StashOf<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: Config> Convert<T::AccountId, Option<T::AccountId>> for StashOf<T> {
+	fn convert(controller: T::AccountId) -> Option<T::AccountId> {
+		Stashes::<T>::get(&controller)
+	}
+}
+
+/// A `Convert` implementation that finds the exposure of a validator of the active era.
+///
+/// Validators that were not elected by this pallet, like the genesis ones, get an empty exposure.
+pub struct ExposureOf<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: Config> Convert<T::AccountId, Option<Exposure<T::AccountId, BalanceOf<T>>>>
+	for ExposureOf<T>
+{
+	fn convert(validator: T::AccountId) -> Option<Exposure<T::AccountId, BalanceOf<T>>> {
+		Some(
+			Pallet::<T>::active_era()
+				.and_then(|era| ErasStakers::<T>::get(era, &validator))
+				.unwrap_or_default(),
+		)
+	}
+}
+
+impl<T: Config> OnOffenceHandler<T::AccountId, ElectedValidator<T>, Weight> for Pallet<T> {
+	fn on_offence(
+		offenders: &[OffenceDetails<T::AccountId, ElectedValidator<T>>],
+		slash_fraction: &[Perbill],
+		slash_session: SessionIndex,
+		disable_strategy: DisableStrategy,
+	) -> Weight {
+		let mut consumed_weight: Weight =
+			T::DbWeight::get().reads(2 + T::BondingDuration::get() as u64);
+		let era = match Self::slashable_era(slash_session) {
+			Some(era) => era,
+			None => {
+				log::warn!(
+					target: LOG_TARGET,
+					"ignoring an offence in session {}, past the bonding duration",
+					slash_session
+				);
+				return consumed_weight
+			},
+		};
+
+		for (details, fraction) in offenders.iter().zip(slash_fraction) {
+			let (stash, exposure) = &details.offender;
+			consumed_weight = consumed_weight.saturating_add(T::DbWeight::get().reads_writes(8, 6));
+
+			// Several offences in the same era only slash the largest fraction of the bond.
+			let already_slashed = SlashedInEra::<T>::get(era, stash);
+			let slashed =
+				Self::slash_bond(stash, (*fraction * exposure.own).saturating_sub(already_slashed));
+			if !slashed.is_zero() {
+				SlashedInEra::<T>::insert(era, stash, already_slashed.saturating_add(slashed));
+				log::warn!(
+					target: LOG_TARGET,
+					"slashed {:?} of {:?} for an offence in session {}",
+					slashed,
+					stash,
+					slash_session
+				);
+				Self::deposit_event(Event::Slashed(stash.clone(), slashed));
+			}
+
+			let disable = match disable_strategy {
+				DisableStrategy::Never => false,
+				DisableStrategy::WhenSlashed => !fraction.is_zero(),
+				DisableStrategy::Always => true,
+			};
+			if disable {
+				if let Some(index) =
+					T::SessionInterface::validators().iter().position(|v| v == stash)
+				{
+					Self::disable_validator(index as u32);
+				}
+			}
+		}
+
+		consumed_weight
+	}
+}
+
+impl<T: Config> frame_support::traits::DisabledValidators for Pallet<T> {
+	fn is_disabled(index: u32) -> bool {
+		Self::disabled_validators().binary_search(&index).is_ok()
+	}
+}
+
+/// The identities of the candidates, usually provided by `pallet_identity`.
+pub trait CandidateIdentity<AccountId> {
+	/// Whether `who` has an identity judged good by a registrar.
+	fn has_judged_identity(who: &AccountId) -> bool;
+	/// The display name of the identity of `who`, if it has one.
+	fn display_name(who: &AccountId) -> Option<Vec<u8>>;
+}
+
+impl<AccountId> CandidateIdentity<AccountId> for () {
+	fn has_judged_identity(_who: &AccountId) -> bool {
+		false
+	}
+
+	fn display_name(_who: &AccountId) -> Option<Vec<u8>> {
+		None
+	}
+}
+
+/// Means for interacting with a specialized version of the `session` trait.
+pub trait SessionInterface<AccountId> {
+	/// Disable the validator at the given index in the current session.
+	fn disable_validator(validator_index: u32) -> bool;
+	/// Get the validators of the current session.
+	fn validators() -> Vec<AccountId>;
+	/// Prune the historical session roots before the session `up_to`, which can no longer be
+	/// used to prove an offence.
+	fn prune_historical_up_to(up_to: SessionIndex);
+}
+
+impl<T> SessionInterface<<T as frame_system::Config>::AccountId> for T
+where
+	T: Config + pallet_session::Config<ValidatorId = <T as frame_system::Config>::AccountId>,
+	T: pallet_session::historical::Config,
+{
+	fn disable_validator(validator_index: u32) -> bool {
+		<pallet_session::Pallet<T>>::disable_index(validator_index)
+	}
+
+	fn validators() -> Vec<<T as frame_system::Config>::AccountId> {
+		<pallet_session::Pallet<T>>::validators()
+	}
+
+	fn prune_historical_up_to(up_to: SessionIndex) {
+		<pallet_session::historical::Pallet<T>>::prune_up_to(up_to)
+	}
+}
@@ -0,0 +1,47 @@
+//! Storage migrations of the pallet.
+
+use super::*;
+use frame_support::{
+	storage::unhashed,
+	traits::{GetStorageVersion, OnRuntimeUpgrade, PalletInfoAccess, StorageVersion},
+	weights::Weight,
+};
+
+pub mod v1 {
+	use super::*;
+
+	/// Migrate a chain that ran the git pallet `asiniscalchi/pallet-staking-ddpos` to this pallet.
+	///
+	/// The storage of the git pallet cannot be decoded with the layout of this pallet, so it is
+	/// removed instead of being read as garbage: the candidates have to bond and vote again, and
+	/// the session keeps its validators until enough candidates are bonded. A chain built from a
+	/// genesis of this pallet is already at version 1 and is not touched.
+	pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let on_chain = Pallet::<T>::on_chain_storage_version();
+			if on_chain >= 1 {
+				log::info!(target: LOG_TARGET, "storage already at {:?}, skipping v1", on_chain);
+				return T::DbWeight::get().reads(1)
+			}
+
+			let prefix =
+				sp_io::hashing::twox_128(<Pallet<T> as PalletInfoAccess>::name().as_bytes());
+			let removed = unhashed::clear_prefix(&prefix, None, None).backend;
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			log::info!(target: LOG_TARGET, "removed {} keys of the git pallet, storage at v1", removed);
+			T::DbWeight::get().reads_writes(1, removed as Weight + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() == 1,
+				"storage version not set to 1"
+			);
+			Ok(())
+		}
+	}
+}
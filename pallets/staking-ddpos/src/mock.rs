@@ -0,0 +1,267 @@
+use crate as pallet_staking_ddpos;
+use frame_support::{
+	parameter_types,
+	traits::{ConstU16, ConstU32, ConstU64, GenesisBuild, Hooks, UnixTime},
+	PalletId,
+};
+use frame_system as system;
+use pallet_session::SessionManager;
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+};
+use sp_staking::SessionIndex;
+
+pub type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+pub type AccountId = u64;
+pub type Balance = u64;
+
+/// Initial free balance of every endowed account.
+pub const INITIAL_BALANCE: Balance = 1_000;
+
+/// Amount minted at the end of every era.
+pub const ERA_REWARD: Balance = 100;
+
+/// Number of eras kept in the era-indexed history.
+pub const HISTORY_DEPTH: u32 = 2;
+
+// Configure a mock runtime to test the pallet.
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Staking: pallet_staking_ddpos::{Pallet, Call, Storage, Config<T>, Event<T>, ValidateUnsigned},
+	}
+);
+
+impl system::Config for Test {
+	type BaseCallFilter = frame_support::traits::Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = ConstU64<250>;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ConstU16<42>;
+	type OnSetCode = ();
+	type MaxConsumers = frame_support::traits::ConstU32<16>;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ConstU32<50>;
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type Balance = Balance;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ConstU64<1>;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+impl pallet_staking_ddpos::Config for Test {
+	type Event = Event;
+	type Currency = Balances;
+	type CurrencyBalance = Balance;
+	type MinimumValidatorCount = ConstU32<1>;
+	type MaximumValidatorCount = ConstU32<2>;
+	type MaxValidators = ConstU32<MAX_VALIDATORS>;
+	type SessionsPerEra = SessionsPerEra;
+	type HistoryDepth = ConstU32<HISTORY_DEPTH>;
+	type EraPayout = TestEraPayout;
+	type UnixTime = Timestamp;
+	type PalletId = StakingPalletId;
+	type Slash = ();
+	type SessionInterface = TestSessionInterface;
+	type CurrentSlot = CurrentSlot;
+	type MaxMissedSlots = ConstU32<MAX_MISSED_SLOTS>;
+	type BondingDuration = ConstU32<BONDING_DURATION>;
+	type MaxUnlockingChunks = ConstU32<MAX_UNLOCKING_CHUNKS>;
+	type MaxVotersPerCandidate = ConstU32<MAX_VOTERS_PER_CANDIDATE>;
+	type MinVote = ConstU64<MIN_VOTE>;
+	type AdminOrigin = system::EnsureRoot<AccountId>;
+	type UnsignedPriority = ConstU64<{ u64::MAX }>;
+	type Identity = TestIdentity;
+	type CommissionChangeDelay = ConstU32<COMMISSION_CHANGE_DELAY>;
+	type WeightInfo = ();
+}
+
+impl<C> system::offchain::SendTransactionTypes<C> for Test
+where
+	Call: From<C>,
+{
+	type OverarchingCall = Call;
+	type Extrinsic = UncheckedExtrinsic;
+}
+
+/// Eras before unbonded funds can be withdrawn.
+pub const BONDING_DURATION: u32 = 2;
+
+/// Maximum number of pending unlocking chunks per stash.
+pub const MAX_UNLOCKING_CHUNKS: u32 = 2;
+
+/// Maximum number of accounts voting for a candidate.
+pub const MAX_VOTERS_PER_CANDIDATE: u32 = 3;
+
+/// The smallest vote for a candidate.
+pub const MIN_VOTE: Balance = 10;
+
+/// Hard limit on the number of validators.
+pub const MAX_VALIDATORS: u32 = 4;
+
+/// Consecutive slots a validator can miss before being disabled.
+pub const MAX_MISSED_SLOTS: u32 = 3;
+
+/// Eras before a commission change takes effect.
+pub const COMMISSION_CHANGE_DELAY: u32 = 1;
+
+parameter_types! {
+	pub static SessionsPerEra: SessionIndex = 1;
+	pub static CurrentSlot: u64 = 0;
+	pub static Now: u64 = 0;
+	pub static Identities: Vec<(AccountId, Vec<u8>, bool)> = vec![];
+	pub static LastEraPayout: Option<(Balance, Balance, u64)> = None;
+	pub const StakingPalletId: PalletId = PalletId(*b"py/ddpos");
+	pub static SessionValidators: Vec<AccountId> = vec![];
+	pub static SessionDisabled: Vec<u32> = vec![];
+	pub static HistoricalPrunedUpTo: Option<SessionIndex> = None;
+}
+
+/// Clock backed by the `Now` test static, in milliseconds.
+pub struct Timestamp;
+
+impl UnixTime for Timestamp {
+	fn now() -> core::time::Duration {
+		core::time::Duration::from_millis(Now::get())
+	}
+}
+
+/// Mints `ERA_REWARD` every era, recording the arguments in `LastEraPayout`.
+pub struct TestEraPayout;
+
+impl crate::EraPayout<Balance> for TestEraPayout {
+	fn era_payout(total_staked: Balance, total_issuance: Balance, era_duration: u64) -> Balance {
+		LAST_ERA_PAYOUT
+			.with(|v| *v.borrow_mut() = Some((total_staked, total_issuance, era_duration)));
+		ERA_REWARD
+	}
+}
+
+/// Identities backed by the `Identities` test static, as `(who, display name, judged)`.
+pub struct TestIdentity;
+
+impl crate::CandidateIdentity<AccountId> for TestIdentity {
+	fn has_judged_identity(who: &AccountId) -> bool {
+		Identities::get().iter().any(|(id, _, judged)| id == who && *judged)
+	}
+
+	fn display_name(who: &AccountId) -> Option<Vec<u8>> {
+		Identities::get()
+			.into_iter()
+			.find(|(id, _, _)| id == who)
+			.map(|(_, name, _)| name)
+	}
+}
+
+/// Session interface backed by the `SessionValidators`, `SessionDisabled` and
+/// `HistoricalPrunedUpTo` test statics.
+pub struct TestSessionInterface;
+
+impl crate::SessionInterface<AccountId> for TestSessionInterface {
+	fn disable_validator(validator_index: u32) -> bool {
+		SESSION_DISABLED.with(|v| v.borrow_mut().push(validator_index));
+		true
+	}
+
+	fn validators() -> Vec<AccountId> {
+		SessionValidators::get()
+	}
+
+	fn prune_historical_up_to(up_to: SessionIndex) {
+		HISTORICAL_PRUNED_UP_TO.with(|v| *v.borrow_mut() = Some(up_to));
+	}
+}
+
+// Build genesis storage according to the mock runtime.
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	new_test_ext_with_staking(Default::default())
+}
+
+// Build genesis storage with the given staking genesis configuration.
+pub fn new_test_ext_with_staking(
+	staking: pallet_staking_ddpos::GenesisConfig<Test>,
+) -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: (1..=10).map(|who| (who, INITIAL_BALANCE)).collect(),
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	staking.assimilate_storage(&mut t).unwrap();
+
+	SESSIONS_PER_ERA.with(|v| *v.borrow_mut() = 1);
+	CURRENT_SLOT.with(|v| *v.borrow_mut() = 0);
+	NOW.with(|v| *v.borrow_mut() = 0);
+	IDENTITIES.with(|v| v.borrow_mut().clear());
+	LAST_ERA_PAYOUT.with(|v| *v.borrow_mut() = None);
+	SESSION_VALIDATORS.with(|v| v.borrow_mut().clear());
+	SESSION_DISABLED.with(|v| v.borrow_mut().clear());
+	HISTORICAL_PRUNED_UP_TO.with(|v| *v.borrow_mut() = None);
+
+	let mut ext: sp_io::TestExternalities = t.into();
+	// Events are not deposited on the genesis block.
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
+
+/// Set the identity of `who`, judged by a registrar or not.
+pub fn set_identity(who: AccountId, display_name: &[u8], judged: bool) {
+	IDENTITIES.with(|v| {
+		let mut identities = v.borrow_mut();
+		identities.retain(|(id, _, _)| *id != who);
+		identities.push((who, display_name.to_vec(), judged));
+	});
+}
+
+/// Set the validators of the current session.
+pub fn set_session_validators(validators: Vec<AccountId>) {
+	SESSION_VALIDATORS.with(|v| *v.borrow_mut() = validators);
+}
+
+/// Rotate to session `index` the way `pallet_session` does: end the previous session, start the
+/// new one and plan the next one.
+pub fn rotate_to_session(index: SessionIndex) {
+	if index > 0 {
+		Staking::end_session(index - 1);
+	}
+	Staking::start_session(index);
+	Staking::new_session(index + 1);
+}
+
+/// Import a block authored in `slot`.
+pub fn author_in_slot(slot: u64) {
+	CURRENT_SLOT.with(|v| *v.borrow_mut() = slot);
+	System::set_block_number(System::block_number() + 1);
+	Staking::on_initialize(System::block_number());
+}
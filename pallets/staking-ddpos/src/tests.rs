@@ -0,0 +1,1168 @@
+use crate::{
+	inflation::compute_total_payout, migrations::v1::MigrateToV1, mock::*, ActiveEra,
+	CandidateInfo, ElectionScore, EraPayout, Error, Event as StakingEvent, Exposure, ExposureOf,
+	GenesisConfig, StashOf, UnlockChunk,
+};
+use codec::Decode;
+use frame_support::{
+	assert_noop, assert_ok,
+	storage::migration::{get_storage_value, put_storage_value},
+	traits::{
+		DisabledValidators, GetStorageVersion, Hooks, LockableCurrency, OnRuntimeUpgrade,
+		WithdrawReasons,
+	},
+	unsigned::ValidateUnsigned,
+};
+use pallet_session::SessionManager;
+use sp_core::offchain::{testing::TestTransactionPoolExt, TransactionPoolExt};
+use sp_runtime::{
+	curve::PiecewiseLinear,
+	traits::Convert,
+	transaction_validity::{InvalidTransaction, TransactionSource},
+	Perbill,
+};
+use sp_staking::offence::{DisableStrategy, OffenceDetails, OnOffenceHandler};
+
+fn last_event() -> StakingEvent<Test> {
+	System::events()
+		.into_iter()
+		.filter_map(|r| if let Event::Staking(e) = r.event { Some(e) } else { None })
+		.last()
+		.expect("an event was deposited")
+}
+
+#[test]
+fn bond_locks_funds() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(1), 50));
+
+		assert_eq!(Staking::candidates(1), Some(150));
+		assert_eq!(Staking::locked(&1), 150);
+		assert_eq!(Balances::usable_balance(&1), INITIAL_BALANCE - 150);
+		assert_eq!(last_event(), StakingEvent::Bonded(1, 50));
+	});
+}
+
+#[test]
+fn bond_fails_without_enough_balance() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Staking::bond(Origin::signed(1), 0), Error::<Test>::ZeroAmount);
+		assert_noop!(
+			Staking::bond(Origin::signed(1), INITIAL_BALANCE + 1),
+			Error::<Test>::InsufficientBalance
+		);
+	});
+}
+
+#[test]
+fn unbonded_funds_are_released_after_the_bonding_duration() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Staking::unbond(Origin::signed(1), 10), Error::<Test>::NotCandidate);
+
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::unbond(Origin::signed(1), 30));
+		assert_eq!(Staking::candidates(1), Some(70));
+		assert_eq!(Staking::unlocking(1), vec![UnlockChunk { value: 30, era: BONDING_DURATION }]);
+		assert_eq!(Staking::locked(&1), 100);
+		assert_eq!(last_event(), StakingEvent::Unbonded(1, 30, BONDING_DURATION));
+
+		// Unbonding more than the bond unbonds the whole bond, in the same chunk.
+		assert_ok!(Staking::unbond(Origin::signed(1), 200));
+		assert_eq!(Staking::candidates(1), None);
+		assert_eq!(Staking::unlocking(1), vec![UnlockChunk { value: 100, era: BONDING_DURATION }]);
+
+		assert_ok!(Staking::withdraw_unbonded(Origin::signed(1)));
+		assert_eq!(Balances::usable_balance(&1), INITIAL_BALANCE - 100);
+
+		ActiveEra::<Test>::put(BONDING_DURATION);
+		assert_ok!(Staking::withdraw_unbonded(Origin::signed(1)));
+		assert!(Staking::unlocking(1).is_empty());
+		assert_eq!(Balances::usable_balance(&1), INITIAL_BALANCE);
+		assert_eq!(last_event(), StakingEvent::Withdrawn(1, 100));
+	});
+}
+
+#[test]
+fn unlocking_chunks_are_bounded() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		for era in 0..MAX_UNLOCKING_CHUNKS {
+			ActiveEra::<Test>::put(era);
+			assert_ok!(Staking::unbond(Origin::signed(1), 10));
+		}
+
+		ActiveEra::<Test>::put(MAX_UNLOCKING_CHUNKS);
+		assert_noop!(Staking::unbond(Origin::signed(1), 10), Error::<Test>::NoMoreChunks);
+	});
+}
+
+#[test]
+fn rebond_cancels_the_latest_unlocking_chunks() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Staking::rebond(Origin::signed(1), 10), Error::<Test>::NoUnlockChunk);
+
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::unbond(Origin::signed(1), 30));
+		ActiveEra::<Test>::put(1);
+		assert_ok!(Staking::unbond(Origin::signed(1), 70));
+		assert_eq!(Staking::candidates(1), None);
+
+		assert_ok!(Staking::rebond(Origin::signed(1), 80));
+		assert_eq!(Staking::candidates(1), Some(80));
+		assert_eq!(Staking::bonded(1), Some(1));
+		assert_eq!(Staking::unlocking(1), vec![UnlockChunk { value: 20, era: BONDING_DURATION }]);
+		assert_eq!(Staking::locked(&1), 100);
+		assert_eq!(last_event(), StakingEvent::Rebonded(1, 80));
+	});
+}
+
+#[test]
+fn unbonding_funds_can_be_slashed() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::unbond(Origin::signed(1), 80));
+
+		assert_eq!(Staking::slash_bond(&1, 50), 50);
+		assert_eq!(Staking::candidates(1), None);
+		assert_eq!(Staking::unlocking(1), vec![UnlockChunk { value: 50, era: BONDING_DURATION }]);
+		assert_eq!(Staking::locked(&1), 50);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 50);
+	});
+}
+
+#[test]
+fn bond_pairs_the_stash_with_itself() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+
+		assert_eq!(Staking::bonded(1), Some(1));
+		assert_eq!(StashOf::<Test>::convert(1), Some(1));
+		assert_eq!(StashOf::<Test>::convert(2), None);
+	});
+}
+
+#[test]
+fn set_controller_changes_the_pairing() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Staking::set_controller(Origin::signed(1), 2), Error::<Test>::NotCandidate);
+
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::set_controller(Origin::signed(1), 2));
+
+		assert_eq!(Staking::bonded(1), Some(2));
+		assert_eq!(StashOf::<Test>::convert(2), Some(1));
+		assert_eq!(StashOf::<Test>::convert(1), None);
+		assert_eq!(last_event(), StakingEvent::ControllerChanged(1, 2));
+
+		// A controller can neither bond nor control another stash.
+		assert_noop!(Staking::bond(Origin::signed(2), 100), Error::<Test>::AlreadyPaired);
+		assert_ok!(Staking::bond(Origin::signed(3), 100));
+		assert_noop!(Staking::set_controller(Origin::signed(3), 2), Error::<Test>::AlreadyPaired);
+		assert_noop!(Staking::set_controller(Origin::signed(3), 1), Error::<Test>::AlreadyPaired);
+	});
+}
+
+#[test]
+fn unbond_removes_the_pairing() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::set_controller(Origin::signed(1), 2));
+		assert_ok!(Staking::unbond(Origin::signed(1), 100));
+
+		assert_eq!(Staking::bonded(1), None);
+		assert_eq!(StashOf::<Test>::convert(2), None);
+		assert_ok!(Staking::bond(Origin::signed(2), 100));
+	});
+}
+
+#[test]
+fn vote_and_unvote() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(Staking::vote(Origin::signed(2), 1, 10), Error::<Test>::NotCandidate);
+
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::vote(Origin::signed(2), 1, 10));
+		assert_ok!(Staking::vote(Origin::signed(2), 1, 20));
+
+		assert_eq!(Staking::votes(2, 1), Some(30));
+		assert_eq!(Staking::votes_received(1), 30);
+		assert_eq!(Staking::votes_of(&2), vec![(1, 30)]);
+		assert_eq!(Staking::locked(&2), 30);
+
+		assert_noop!(Staking::unvote(Origin::signed(3), 1), Error::<Test>::NoVote);
+		assert_ok!(Staking::unvote(Origin::signed(2), 1));
+
+		assert_eq!(Staking::votes(2, 1), None);
+		assert_eq!(Staking::votes_received(1), 0);
+		assert_eq!(Balances::usable_balance(&2), INITIAL_BALANCE);
+		assert_eq!(last_event(), StakingEvent::Unvoted(2, 1, 30));
+	});
+}
+
+#[test]
+fn votes_below_the_minimum_are_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_noop!(
+			Staking::vote(Origin::signed(2), 1, MIN_VOTE - 1),
+			Error::<Test>::VoteTooSmall
+		);
+
+		// Once at the minimum, a vote can be topped up by any amount.
+		assert_ok!(Staking::vote(Origin::signed(2), 1, MIN_VOTE));
+		assert_ok!(Staking::vote(Origin::signed(2), 1, 1));
+		assert_eq!(Staking::votes(2, 1), Some(MIN_VOTE + 1));
+	});
+}
+
+#[test]
+fn candidates_have_a_maximum_of_voters() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		for voter in 2..2 + MAX_VOTERS_PER_CANDIDATE as AccountId {
+			assert_ok!(Staking::vote(Origin::signed(voter), 1, MIN_VOTE));
+		}
+		assert_eq!(Staking::voter_count(1), MAX_VOTERS_PER_CANDIDATE);
+		assert_noop!(Staking::vote(Origin::signed(9), 1, MIN_VOTE), Error::<Test>::TooManyVoters);
+
+		// The voters can still add to their votes, and leaving frees a place.
+		assert_ok!(Staking::vote(Origin::signed(2), 1, 10));
+		assert_ok!(Staking::unvote(Origin::signed(2), 1));
+		assert_eq!(Staking::voter_count(1), MAX_VOTERS_PER_CANDIDATE - 1);
+		assert_ok!(Staking::vote(Origin::signed(9), 1, MIN_VOTE));
+	});
+}
+
+#[test]
+fn locked_funds_can_be_staked() {
+	new_test_ext().execute_with(|| {
+		// A vesting schedule locks most of the balance.
+		Balances::set_lock(*b"vesting ", &1, 800, WithdrawReasons::all());
+		assert_ok!(Staking::bond(Origin::signed(2), 100));
+
+		assert_ok!(Staking::bond(Origin::signed(1), 500));
+		assert_ok!(Staking::vote(Origin::signed(1), 2, 200));
+
+		// The locks overlap instead of stacking.
+		assert_eq!(Balances::locks(&1).len(), 2);
+		assert_eq!(Balances::usable_balance(&1), INITIAL_BALANCE - 800);
+		assert_noop!(Staking::bond(Origin::signed(1), 400), Error::<Test>::InsufficientBalance);
+		assert_ok!(Staking::bond(Origin::signed(1), 300));
+		assert_eq!(Balances::usable_balance(&1), 0);
+	});
+}
+
+#[test]
+fn ranking_orders_by_total_backing() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(2), 200));
+		assert_ok!(Staking::bond(Origin::signed(3), 100));
+		assert_ok!(Staking::vote(Origin::signed(4), 3, 150));
+
+		assert_eq!(
+			Staking::ranking(),
+			vec![
+				CandidateInfo {
+					who: 3,
+					bond: 100,
+					total: 250,
+					commission: Perbill::zero(),
+					display_name: None
+				},
+				CandidateInfo {
+					who: 2,
+					bond: 200,
+					total: 200,
+					commission: Perbill::zero(),
+					display_name: None
+				},
+				CandidateInfo {
+					who: 1,
+					bond: 100,
+					total: 100,
+					commission: Perbill::zero(),
+					display_name: None
+				},
+			]
+		);
+	});
+}
+
+#[test]
+fn new_session_elects_best_candidates() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Staking::new_session(1), None);
+
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(2), 200));
+		assert_ok!(Staking::bond(Origin::signed(3), 300));
+
+		assert_eq!(Staking::new_session(1), Some(vec![3, 2]));
+		assert_eq!(Staking::current_era(), Some(0));
+		assert_eq!(Staking::next_validators(), vec![3, 2]);
+		assert!(Staking::current_validators().is_empty());
+		assert_eq!(last_event(), StakingEvent::ValidatorsElected(0, vec![3, 2]));
+
+		Staking::start_session(1);
+		assert_eq!(Staking::current_session(), 1);
+		assert_eq!(Staking::active_era(), Some(0));
+		assert_eq!(Staking::current_validators(), vec![3, 2]);
+		assert_eq!(last_event(), StakingEvent::EraStarted(0));
+	});
+}
+
+#[test]
+fn validators_only_change_with_a_new_era() {
+	new_test_ext().execute_with(|| {
+		SessionsPerEra::set(3);
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_eq!(Staking::new_session(0), Some(vec![1]));
+		rotate_to_session(0);
+		assert_eq!(Staking::active_era(), Some(0));
+
+		// A better candidate does not replace the validator within the era.
+		assert_ok!(Staking::bond(Origin::signed(2), 200));
+		rotate_to_session(1);
+		assert_eq!(Staking::current_era(), Some(0));
+		assert_eq!(Staking::current_validators(), vec![1]);
+
+		// The next era is planned with the first session of the era, and started with it.
+		rotate_to_session(2);
+		assert_eq!(Staking::current_era(), Some(1));
+		assert_eq!(Staking::eras_start_session_index(1), Some(3));
+		assert_eq!(Staking::next_validators(), vec![2, 1]);
+		assert_eq!(Staking::current_validators(), vec![1]);
+
+		rotate_to_session(3);
+		assert_eq!(Staking::active_era(), Some(1));
+		assert_eq!(Staking::current_validators(), vec![2, 1]);
+	});
+}
+
+#[test]
+fn era_history_is_pruned() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_eq!(Staking::new_session(0), Some(vec![1]));
+		for session in 0..HISTORY_DEPTH {
+			rotate_to_session(session);
+		}
+		assert_eq!(Staking::active_era(), Some(HISTORY_DEPTH - 1));
+		assert!(Staking::eras_stakers(0, 1).is_some());
+		assert!(Staking::eras_reward(0).is_some());
+
+		rotate_to_session(HISTORY_DEPTH);
+		assert_eq!(Staking::eras_stakers(0, 1), None);
+		assert_eq!(Staking::eras_validators(0), Vec::<AccountId>::new());
+		assert_eq!(Staking::eras_start_session_index(0), None);
+		assert_eq!(Staking::eras_reward(0), None);
+		assert_eq!(Staking::eras_validator_reward(0, 1), None);
+		assert!(Staking::eras_stakers(1, 1).is_some());
+	});
+}
+
+#[test]
+fn new_session_keeps_validators_below_minimum() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::set_maximum_validators(Origin::root(), 3));
+		assert_ok!(Staking::set_minimum_validators(Origin::root(), 2));
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+
+		assert_eq!(Staking::new_session(1), None);
+	});
+}
+
+#[test]
+fn validator_count_bounds_are_consistent() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Staking::set_minimum_validators(Origin::signed(1), 1),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Staking::set_minimum_validators(Origin::root(), 0),
+			Error::<Test>::InvalidValidatorCount
+		);
+		assert_noop!(
+			Staking::set_minimum_validators(Origin::root(), 3),
+			Error::<Test>::InvalidValidatorCount
+		);
+
+		assert_ok!(Staking::set_minimum_validators(Origin::root(), 2));
+		assert_noop!(
+			Staking::set_maximum_validators(Origin::root(), 1),
+			Error::<Test>::InvalidValidatorCount
+		);
+		assert_eq!(last_event(), StakingEvent::MinimumValidatorsSet(2));
+
+		assert_noop!(
+			Staking::set_maximum_validators(Origin::root(), MAX_VALIDATORS + 1),
+			Error::<Test>::TooManyValidators
+		);
+		assert_ok!(Staking::set_maximum_validators(Origin::root(), MAX_VALIDATORS));
+	});
+}
+
+#[test]
+fn integrity_test_accepts_the_mock_config() {
+	new_test_ext().execute_with(|| Staking::integrity_test());
+}
+
+#[test]
+#[should_panic(expected = "Genesis maximum validators is above `MaxValidators`.")]
+fn genesis_maximum_is_bounded() {
+	new_test_ext_with_staking(GenesisConfig {
+		maximum_validators: MAX_VALIDATORS + 1,
+		..Default::default()
+	});
+}
+
+#[test]
+fn migration_to_v1_removes_the_git_pallet_storage() {
+	new_test_ext().execute_with(|| {
+		// Storage left by the git pallet, which has no storage version.
+		put_storage_value(b"Staking", b"Candidates", b"", 100u64);
+		assert_eq!(Staking::on_chain_storage_version(), 0);
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(get_storage_value::<u64>(b"Staking", b"Candidates", b""), None);
+		assert_eq!(Staking::on_chain_storage_version(), 1);
+
+		// The storage of this pallet is kept once migrated.
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(Staking::candidates(1), Some(100));
+	});
+}
+
+#[test]
+fn candidates_info_has_the_display_names() {
+	new_test_ext().execute_with(|| {
+		set_identity(1, b"alice", false);
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(2), 200));
+
+		assert_eq!(Staking::ranking()[1].display_name, None);
+		assert_eq!(
+			Staking::candidates_info(),
+			vec![
+				CandidateInfo {
+					who: 2,
+					bond: 200,
+					total: 200,
+					commission: Perbill::zero(),
+					display_name: None
+				},
+				CandidateInfo {
+					who: 1,
+					bond: 100,
+					total: 100,
+					commission: Perbill::zero(),
+					display_name: Some(b"alice".to_vec())
+				},
+			]
+		);
+	});
+}
+
+#[test]
+fn candidacy_can_require_a_judged_identity() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::set_require_identity(Origin::root(), true));
+		assert_eq!(last_event(), StakingEvent::IdentityRequirementSet(true));
+
+		// Existing candidates can still add to their bond.
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+
+		assert_noop!(Staking::bond(Origin::signed(2), 100), Error::<Test>::NoIdentity);
+		set_identity(2, b"bob", false);
+		assert_noop!(Staking::bond(Origin::signed(2), 100), Error::<Test>::NoIdentity);
+		set_identity(2, b"bob", true);
+		assert_ok!(Staking::bond(Origin::signed(2), 100));
+
+		// Leaving the candidate pool and rebonding is checked again.
+		assert_ok!(Staking::unbond(Origin::signed(1), 200));
+		assert_noop!(Staking::rebond(Origin::signed(1), 200), Error::<Test>::NoIdentity);
+	});
+}
+
+#[test]
+fn only_admin_sets_the_identity_requirement() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Staking::set_require_identity(Origin::signed(1), true),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert!(!Staking::require_identity());
+	});
+}
+
+#[test]
+fn genesis_candidates_do_not_need_an_identity() {
+	new_test_ext_with_staking(GenesisConfig {
+		candidates: vec![(1, 1, 100)],
+		require_identity: true,
+		..Default::default()
+	})
+	.execute_with(|| {
+		assert_eq!(Staking::candidates(1), Some(100));
+		assert!(Staking::require_identity());
+	});
+}
+
+#[test]
+fn election_records_exposures() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::vote(Origin::signed(3), 1, 60));
+		assert_ok!(Staking::vote(Origin::signed(4), 1, 40));
+
+		assert_eq!(Staking::new_session(1), Some(vec![1]));
+		assert_eq!(ExposureOf::<Test>::convert(1), Some(Exposure::default()));
+
+		Staking::start_session(1);
+		let exposure = Exposure { total: 200, own: 100, others: vec![(3, 60), (4, 40)] };
+		assert_eq!(Staking::eras_stakers(0, 1), Some(exposure.clone()));
+		assert_eq!(ExposureOf::<Test>::convert(1), Some(exposure));
+	});
+}
+
+#[test]
+fn end_of_era_rewards_validators_and_voters() {
+	new_test_ext().execute_with(|| {
+		// Nothing is paid while running on the genesis validators.
+		Staking::end_session(0);
+		assert!(System::events().is_empty());
+
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(2), 300));
+		assert_ok!(Staking::vote(Origin::signed(3), 1, 100));
+		assert_eq!(Staking::new_session(1), Some(vec![2, 1]));
+		Staking::start_session(1);
+
+		// Nothing is paid until the next era is planned.
+		let issuance = Balances::total_issuance();
+		Staking::end_session(1);
+		assert_eq!(Balances::total_issuance(), issuance);
+
+		assert_eq!(Staking::new_session(2), Some(vec![2, 1]));
+		Staking::end_session(1);
+
+		// The inflation is minted into the reward pot, and each validator receives half of it, to
+		// be claimed.
+		assert_eq!(Balances::total_issuance(), issuance + ERA_REWARD);
+		assert_eq!(Staking::eras_reward(0), Some(ERA_REWARD));
+		assert_eq!(last_event(), StakingEvent::EraPaid(0, ERA_REWARD));
+		assert_eq!(Staking::eras_validator_reward(0, 1), Some(ERA_REWARD / 2));
+		assert_eq!(Staking::unclaimed_rewards(), ERA_REWARD);
+
+		// Any account can claim a payout, shared with the voters of the validator by stake.
+		assert_ok!(Staking::payout_stakers(Origin::signed(5), 1, 0));
+		assert_ok!(Staking::payout_stakers(Origin::signed(5), 2, 0));
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 25);
+		assert_eq!(Balances::free_balance(2), INITIAL_BALANCE + 50);
+		assert_eq!(Balances::free_balance(3), INITIAL_BALANCE + 25);
+		assert_eq!(last_event(), StakingEvent::Rewarded(2, 50));
+		assert_eq!(Balances::total_issuance(), issuance + ERA_REWARD);
+		assert_eq!(Staking::unclaimed_rewards(), 0);
+		// The reward pot only keeps its existential deposit.
+		assert_eq!(Balances::free_balance(Staking::reward_pot()), 1);
+
+		assert_noop!(
+			Staking::payout_stakers(Origin::signed(5), 1, 0),
+			Error::<Test>::NothingToClaim
+		);
+		assert_noop!(
+			Staking::payout_stakers(Origin::signed(5), 3, 0),
+			Error::<Test>::NothingToClaim
+		);
+	});
+}
+
+#[test]
+fn unclaimed_rewards_expire_with_the_history() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_eq!(Staking::new_session(0), Some(vec![1]));
+		rotate_to_session(0);
+		rotate_to_session(1);
+		assert_eq!(Staking::eras_validator_reward(0, 1), Some(ERA_REWARD));
+
+		// The reward can be claimed until the era leaves the history.
+		rotate_to_session(HISTORY_DEPTH);
+		assert_noop!(
+			Staking::payout_stakers(Origin::signed(1), 1, 0),
+			Error::<Test>::NothingToClaim
+		);
+		assert_ok!(Staking::payout_stakers(Origin::signed(1), 1, 1));
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + ERA_REWARD);
+		assert_eq!(Staking::unclaimed_rewards(), 0);
+
+		// The expired reward goes back to the pot, and is set aside with the next era reward.
+		rotate_to_session(HISTORY_DEPTH + 1);
+		assert_eq!(Staking::eras_validator_reward(HISTORY_DEPTH, 1), Some(2 * ERA_REWARD));
+	});
+}
+
+#[test]
+fn era_inflation_is_minted_into_the_reward_pot() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(2), 300));
+		assert_ok!(Staking::vote(Origin::signed(3), 1, 100));
+		assert_eq!(Staking::new_session(1), Some(vec![2, 1]));
+		Staking::start_session(1);
+		assert_eq!(Staking::active_era_start(), None);
+
+		// The era starts at the timestamp of its first block.
+		Now::set(1_000);
+		Staking::on_finalize(1);
+		Now::set(2_000);
+		Staking::on_finalize(2);
+		assert_eq!(Staking::active_era_start(), Some(1_000));
+
+		// Candidates and votes outside the validators of the era count in the staking ratio.
+		assert_ok!(Staking::bond(Origin::signed(4), 50));
+		assert_ok!(Staking::vote(Origin::signed(5), 4, 25));
+
+		let issuance = Balances::total_issuance();
+		Now::set(7_000);
+		assert_eq!(Staking::new_session(2), Some(vec![2, 1]));
+		Staking::end_session(1);
+
+		// The payout is computed from the total bonded stake and the duration of the era.
+		assert_eq!(LastEraPayout::get(), Some((575, issuance, 6_000)));
+		assert!(System::events()
+			.into_iter()
+			.any(|r| r.event == Event::Staking(StakingEvent::RewardMinted(0, ERA_REWARD))));
+		assert_eq!(Balances::total_issuance(), issuance + ERA_REWARD);
+
+		Staking::start_session(2);
+		assert_eq!(Staking::active_era_start(), None);
+	});
+}
+
+#[test]
+fn reward_remainders_stay_in_the_pot() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::vote(Origin::signed(3), 1, 100));
+		assert_ok!(Staking::vote(Origin::signed(4), 1, 100));
+		assert_eq!(Staking::new_session(1), Some(vec![1]));
+		Staking::start_session(1);
+		assert_eq!(Staking::new_session(2), Some(vec![1]));
+		Staking::end_session(1);
+		assert_ok!(Staking::payout_stakers(Origin::signed(1), 1, 0));
+
+		// Each third of the reward is rounded down.
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 33);
+		assert_eq!(Balances::free_balance(3), INITIAL_BALANCE + 33);
+		assert_eq!(Balances::free_balance(4), INITIAL_BALANCE + 33);
+		assert_eq!(Balances::free_balance(Staking::reward_pot()), 2);
+
+		// The remainder is set aside with the next era reward.
+		Staking::start_session(2);
+		assert_eq!(Staking::new_session(3), Some(vec![1]));
+		Staking::end_session(2);
+		assert_eq!(Staking::eras_validator_reward(1, 1), Some(ERA_REWARD + 1));
+	});
+}
+
+#[test]
+fn era_payout_follows_the_inflation_curve() {
+	// 10% yearly inflation when nothing is staked, 20% at the ideal 50% staked, 5% when
+	// everything is staked.
+	static CURVE: PiecewiseLinear<'static> = PiecewiseLinear {
+		points: &[
+			(Perbill::from_parts(0), Perbill::from_parts(100_000_000)),
+			(Perbill::from_parts(500_000_000), Perbill::from_parts(200_000_000)),
+			(Perbill::from_parts(1_000_000_000), Perbill::from_parts(50_000_000)),
+		],
+		maximum: Perbill::from_parts(200_000_000),
+	};
+	const YEAR: u64 = 36525 * 24 * 3600 * 1000 / 100;
+
+	assert_eq!(compute_total_payout(&CURVE, 500u64, 1_000, YEAR), 200);
+	assert_eq!(compute_total_payout(&CURVE, 0u64, 1_000, YEAR / 2), 50);
+	assert_eq!(compute_total_payout(&CURVE, 1_000u64, 1_000, YEAR), 50);
+	assert_eq!(compute_total_payout(&CURVE, 500u64, 1_000, 0), 0);
+	assert_eq!(<() as EraPayout<u64>>::era_payout(500, 1_000, YEAR), 0);
+}
+
+#[test]
+fn commission_changes_are_delayed() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Staking::set_commission(Origin::signed(1), Perbill::from_percent(10)),
+			Error::<Test>::NotCandidate
+		);
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::set_commission(Origin::signed(1), Perbill::from_percent(10)));
+		assert_eq!(
+			last_event(),
+			StakingEvent::CommissionChangeScheduled(1, Perbill::from_percent(10), 1)
+		);
+		assert_eq!(Staking::pending_commission(1), Some((Perbill::from_percent(10), 1)));
+
+		rotate_to_session(0);
+		rotate_to_session(1);
+		assert_eq!(Staking::active_era(), Some(0));
+		assert_eq!(Staking::commission(1), Perbill::zero());
+
+		rotate_to_session(2);
+		assert_eq!(Staking::active_era(), Some(1));
+		assert_eq!(Staking::commission(1), Perbill::from_percent(10));
+		assert_eq!(Staking::pending_commission(1), None);
+		assert_eq!(Staking::ranking()[0].commission, Perbill::from_percent(10));
+		assert!(System::events().into_iter().any(|r| r.event ==
+			Event::Staking(StakingEvent::CommissionSet(1, Perbill::from_percent(10)))));
+	});
+}
+
+#[test]
+fn validators_keep_their_commission_of_the_rewards() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::vote(Origin::signed(3), 1, 100));
+		assert_ok!(Staking::set_commission(Origin::signed(1), Perbill::from_percent(10)));
+		rotate_to_session(0);
+		rotate_to_session(1);
+
+		// The change is not in effect in era 0, and the rewards keep the commission of their era.
+		rotate_to_session(2);
+		assert_eq!(Staking::eras_validator_commission(0, 1), Perbill::zero());
+		rotate_to_session(3);
+		assert_eq!(Staking::eras_validator_commission(1, 1), Perbill::from_percent(10));
+
+		assert_ok!(Staking::payout_stakers(Origin::signed(5), 1, 0));
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 50);
+		assert_eq!(Balances::free_balance(3), INITIAL_BALANCE + 50);
+
+		// The validator keeps 10 and shares the other 90 with its voter.
+		assert_ok!(Staking::payout_stakers(Origin::signed(5), 1, 1));
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE + 50 + 55);
+		assert_eq!(Balances::free_balance(3), INITIAL_BALANCE + 50 + 45);
+		assert_eq!(Staking::eras_validator_commission(1, 1), Perbill::zero());
+		assert_eq!(Staking::eras_reward(1), Some(ERA_REWARD));
+	});
+}
+
+#[test]
+fn on_offence_slashes_the_bond() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::vote(Origin::signed(3), 1, 100));
+		assert_eq!(Staking::new_session(1), Some(vec![1]));
+		Staking::start_session(1);
+
+		let issuance = Balances::total_issuance();
+		let offender = (1, Staking::eras_stakers(0, 1).unwrap());
+		Staking::on_offence(
+			&[OffenceDetails { offender, reporters: vec![] }],
+			&[Perbill::from_percent(10)],
+			1,
+			DisableStrategy::WhenSlashed,
+		);
+
+		// Only the validator's own bond is slashed, and the slashed amount is burnt.
+		assert_eq!(Staking::candidates(1), Some(90));
+		assert_eq!(Staking::locked(&1), 90);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 10);
+		assert_eq!(Balances::free_balance(3), INITIAL_BALANCE);
+		assert_eq!(Balances::total_issuance(), issuance - 10);
+		assert_eq!(last_event(), StakingEvent::Slashed(1, 10));
+	});
+}
+
+#[test]
+fn offences_of_an_era_slash_the_largest_fraction_once() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_eq!(Staking::new_session(1), Some(vec![1]));
+		Staking::start_session(1);
+
+		let offender = (1, Staking::eras_stakers(0, 1).unwrap());
+		let report = |fraction| {
+			Staking::on_offence(
+				&[OffenceDetails { offender: offender.clone(), reporters: vec![] }],
+				&[Perbill::from_percent(fraction)],
+				1,
+				DisableStrategy::Never,
+			)
+		};
+		report(10);
+		assert_eq!(Staking::slashed_in_era(0, 1), 10);
+
+		// A smaller or equal fraction slashes nothing more, a larger one only the difference.
+		report(10);
+		report(5);
+		assert_eq!(Staking::candidates(1), Some(90));
+		report(30);
+		assert_eq!(Staking::candidates(1), Some(70));
+		assert_eq!(Staking::slashed_in_era(0, 1), 30);
+		assert_eq!(last_event(), StakingEvent::Slashed(1, 20));
+	});
+}
+
+#[test]
+fn offences_past_the_bonding_duration_are_not_slashed() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_eq!(Staking::new_session(0), Some(vec![1]));
+		rotate_to_session(0);
+		let offender = (1, Staking::eras_stakers(0, 1).unwrap());
+		Staking::on_offence(
+			&[OffenceDetails { offender: offender.clone(), reporters: vec![] }],
+			&[Perbill::from_percent(10)],
+			0,
+			DisableStrategy::Never,
+		);
+		assert_eq!(HistoricalPrunedUpTo::get(), None);
+
+		for session in 1..=BONDING_DURATION + 1 {
+			rotate_to_session(session);
+		}
+		// The sessions and slashes of era 0 are pruned once era 1 is the oldest slashable one.
+		assert_eq!(HistoricalPrunedUpTo::get(), Some(1));
+		assert_eq!(Staking::slashed_in_era(0, 1), 0);
+
+		Staking::on_offence(
+			&[OffenceDetails { offender, reporters: vec![] }],
+			&[Perbill::from_percent(20)],
+			0,
+			DisableStrategy::Never,
+		);
+		assert_eq!(Staking::candidates(1), Some(90));
+	});
+}
+
+#[test]
+fn full_slash_removes_the_candidate() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_eq!(Staking::new_session(1), Some(vec![1]));
+		Staking::start_session(1);
+
+		let offender = (1, Staking::eras_stakers(0, 1).unwrap());
+		Staking::on_offence(
+			&[OffenceDetails { offender, reporters: vec![] }],
+			&[Perbill::one()],
+			1,
+			DisableStrategy::WhenSlashed,
+		);
+
+		assert_eq!(Staking::candidates(1), None);
+		assert_eq!(Staking::locked(&1), 0);
+		assert_eq!(Balances::free_balance(1), INITIAL_BALANCE - 100);
+	});
+}
+
+#[test]
+fn missing_consecutive_slots_disables_validator() {
+	new_test_ext().execute_with(|| {
+		set_session_validators(vec![1, 2, 3]);
+
+		// The validator at index 1 misses its slots 10, 13 and 16.
+		for slot in [9, 11, 12, 14, 15] {
+			author_in_slot(slot);
+		}
+		assert_eq!(Staking::missed_slots(), vec![0, 2, 0]);
+		assert!(!Staking::is_disabled(1));
+
+		author_in_slot(17);
+		assert!(Staking::is_disabled(1));
+		assert_eq!(Staking::disabled_validators(), vec![1]);
+		assert_eq!(SessionDisabled::get(), vec![1]);
+		assert_eq!(last_event(), StakingEvent::ValidatorDisabled(2));
+	});
+}
+
+#[test]
+fn producing_a_block_resets_missed_slots() {
+	new_test_ext().execute_with(|| {
+		set_session_validators(vec![1, 2]);
+
+		// The validator at index 1 misses slots 3 and 7 but authors slot 5.
+		for slot in [2, 4, 5, 6, 8] {
+			author_in_slot(slot);
+		}
+		assert_eq!(Staking::missed_slots(), vec![0, 1]);
+		assert!(Staking::disabled_validators().is_empty());
+	});
+}
+
+#[test]
+fn new_election_enables_validators_again() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		set_session_validators(vec![1, 2]);
+		assert!(Staking::disable_validator(0));
+		assert!(!Staking::disable_validator(0));
+
+		// A session without a new election keeps the validator disabled.
+		assert_ok!(Staking::set_maximum_validators(Origin::root(), 2));
+		assert_ok!(Staking::set_minimum_validators(Origin::root(), 2));
+		assert_eq!(Staking::new_session(1), None);
+		Staking::start_session(1);
+		assert!(Staking::is_disabled(0));
+
+		assert_ok!(Staking::bond(Origin::signed(2), 100));
+		assert_eq!(Staking::new_session(2), Some(vec![1, 2]));
+		Staking::start_session(2);
+		assert!(!Staking::is_disabled(0));
+		assert!(Staking::missed_slots().is_empty());
+	});
+}
+
+#[test]
+fn last_enabled_validator_is_never_disabled() {
+	new_test_ext().execute_with(|| {
+		set_session_validators(vec![1, 2]);
+
+		assert!(Staking::disable_validator(1));
+		assert!(!Staking::disable_validator(0));
+		assert_eq!(Staking::disabled_validators(), vec![1]);
+	});
+}
+
+#[test]
+fn on_offence_disables_the_offender() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(2), 100));
+		assert_eq!(Staking::new_session(1), Some(vec![1, 2]));
+		Staking::start_session(1);
+		set_session_validators(vec![1, 2]);
+
+		let offender = (2, Staking::eras_stakers(0, 2).unwrap());
+		Staking::on_offence(
+			&[OffenceDetails { offender: offender.clone(), reporters: vec![] }],
+			&[Perbill::zero()],
+			1,
+			DisableStrategy::WhenSlashed,
+		);
+		assert!(Staking::disabled_validators().is_empty());
+
+		Staking::on_offence(
+			&[OffenceDetails { offender, reporters: vec![] }],
+			&[Perbill::zero()],
+			1,
+			DisableStrategy::Always,
+		);
+		assert_eq!(Staking::disabled_validators(), vec![1]);
+		assert_eq!(Staking::candidates(2), Some(100));
+	});
+}
+
+#[test]
+fn offchain_worker_submits_the_election() {
+	let (pool, state) = TestTransactionPoolExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(TransactionPoolExt::new(pool));
+
+	ext.execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(2), 200));
+		assert_ok!(Staking::vote(Origin::signed(3), 1, 150));
+		Staking::offchain_worker(1);
+
+		let transactions = state.read().transactions.clone();
+		assert_eq!(transactions.len(), 1);
+		let xt = UncheckedExtrinsic::decode(&mut &*transactions[0]).unwrap();
+		assert_eq!(xt.signature, None);
+
+		let solution = Staking::compute_solution().unwrap();
+		assert_eq!(solution.era, 0);
+		assert_eq!(solution.score.minimal_stake, 200);
+		assert_eq!(solution.score.sum_stake, 450);
+		assert_eq!(
+			xt.call,
+			Call::Staking(crate::Call::submit_election_solution { solution: solution.clone() })
+		);
+
+		// Nothing is submitted once the solution is queued.
+		assert_ok!(Staking::submit_election_solution(Origin::none(), solution));
+		Staking::offchain_worker(2);
+		assert_eq!(state.read().transactions.len(), 1);
+	});
+}
+
+#[test]
+fn queued_solution_is_used_for_the_next_era() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(2), 200));
+		let solution = Staking::compute_solution().unwrap();
+
+		assert_noop!(
+			Staking::submit_election_solution(Origin::signed(1), solution.clone()),
+			sp_runtime::DispatchError::BadOrigin
+		);
+		assert_ok!(Staking::submit_election_solution(Origin::none(), solution.clone()));
+		assert_eq!(Staking::queued_solution(), Some(solution.clone()));
+		assert_eq!(last_event(), StakingEvent::SolutionStored(0));
+		assert_noop!(
+			Staking::submit_election_solution(Origin::none(), solution),
+			Error::<Test>::WeakSolution
+		);
+
+		assert_eq!(Staking::new_session(1), Some(vec![2, 1]));
+		assert_eq!(Staking::queued_solution(), None);
+	});
+}
+
+#[test]
+fn invalid_solutions_are_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(2), 200));
+		assert_ok!(Staking::vote(Origin::signed(3), 1, 50));
+		let solution = Staking::compute_solution().unwrap();
+
+		let mut wrong_era = solution.clone();
+		wrong_era.era = 1;
+		assert_noop!(
+			Staking::submit_election_solution(Origin::none(), wrong_era),
+			Error::<Test>::WrongEra
+		);
+
+		// Leaving out a candidate would raise the minimal backing.
+		let mut partial = solution.clone();
+		partial.winners.truncate(1);
+		partial.score.minimal_stake = 200;
+		partial.score.sum_stake = 200;
+		assert_noop!(
+			Staking::submit_election_solution(Origin::none(), partial),
+			Error::<Test>::WrongSolutionSize
+		);
+
+		let mut duplicate = solution.clone();
+		duplicate.winners[1] = duplicate.winners[0].clone();
+		assert_noop!(
+			Staking::submit_election_solution(Origin::none(), duplicate),
+			Error::<Test>::InvalidSolution
+		);
+
+		let mut inflated = solution.clone();
+		inflated.winners[1].1.others[0].1 = 500;
+		inflated.winners[1].1.total = 600;
+		assert_noop!(
+			Staking::submit_election_solution(Origin::none(), inflated),
+			Error::<Test>::InvalidSolution
+		);
+
+		let mut overscored = solution.clone();
+		overscored.score.minimal_stake = 1_000;
+		assert_noop!(
+			Staking::submit_election_solution(Origin::none(), overscored),
+			Error::<Test>::InvalidSolution
+		);
+
+		// Only the local offchain worker can submit solutions to the pool.
+		let call = crate::Call::submit_election_solution { solution };
+		assert_eq!(
+			Staking::validate_unsigned(TransactionSource::External, &call),
+			Err(InvalidTransaction::Call.into())
+		);
+		assert!(Staking::validate_unsigned(TransactionSource::Local, &call).is_ok());
+	});
+}
+
+#[test]
+fn solutions_must_elect_the_best_backed_candidates() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(2), 200));
+		let solution = Staking::compute_solution().unwrap();
+
+		// The solution still matches the bonds of its winners, but leaves out a better candidate.
+		assert_ok!(Staking::bond(Origin::signed(3), 150));
+		assert_noop!(
+			Staking::submit_election_solution(Origin::none(), solution),
+			Error::<Test>::SuboptimalSolution
+		);
+
+		// A candidate backed as much as the least backed winner can be left out.
+		assert_ok!(Staking::bond(Origin::signed(4), 150));
+		let mut tied = Staking::compute_solution().unwrap();
+		assert_eq!(tied.winners.iter().map(|(who, _)| *who).collect::<Vec<_>>(), vec![2, 3]);
+		tied.winners[1] = (4, Exposure { total: 150, own: 150, others: vec![] });
+		assert_ok!(Staking::submit_election_solution(Origin::none(), tied));
+	});
+}
+
+#[test]
+fn backing_changes_update_the_queued_solution() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(2), 200));
+		assert_ok!(Staking::bond(Origin::signed(3), 50));
+		let solution = Staking::compute_solution().unwrap();
+		assert_ok!(Staking::submit_election_solution(Origin::none(), solution));
+
+		// Votes for a winner are applied to its exposure.
+		assert_ok!(Staking::vote(Origin::signed(4), 1, 150));
+		let queued = Staking::queued_solution().unwrap();
+		assert_ok!(Staking::check_solution(&queued));
+		assert_eq!(queued.score, Staking::compute_solution().unwrap().score);
+		assert_eq!(
+			queued.winners[1],
+			(1, Exposure { total: 250, own: 100, others: vec![(4, 150)] })
+		);
+
+		// A candidate left out still backed less than the winners keeps the solution.
+		assert_ok!(Staking::bond(Origin::signed(3), 100));
+		let queued = Staking::queued_solution().unwrap();
+		assert_ok!(Staking::check_solution(&queued));
+		assert_eq!(queued.score, ElectionScore { minimal_stake: 200, sum_stake: 450 });
+
+		// Overtaking the least backed winner drops it.
+		assert_ok!(Staking::vote(Origin::signed(5), 3, 60));
+		assert_eq!(Staking::queued_solution(), None);
+	});
+}
+
+#[test]
+fn outdated_solution_falls_back_to_the_on_chain_election() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Staking::bond(Origin::signed(1), 100));
+		assert_ok!(Staking::bond(Origin::signed(2), 200));
+		let solution = Staking::compute_solution().unwrap();
+		assert_ok!(Staking::submit_election_solution(Origin::none(), solution));
+
+		assert_ok!(Staking::set_maximum_validators(Origin::root(), 1));
+		assert_eq!(Staking::new_session(1), Some(vec![2]));
+		assert_eq!(Staking::eras_stakers(0, 1), None);
+	});
+}
+
+#[test]
+fn genesis_bonds_candidates_and_votes() {
+	new_test_ext_with_staking(GenesisConfig {
+		candidates: vec![(1, 1, 100), (2, 4, 200)],
+		votes: vec![(3, 1, 150)],
+		minimum_validators: 1,
+		maximum_validators: 3,
+		require_identity: false,
+	})
+	.execute_with(|| {
+		assert_eq!(Staking::minimum_validators(), 1);
+		assert_eq!(Staking::maximum_validators(), 3);
+		assert_eq!(Staking::candidates(1), Some(100));
+		assert_eq!(Staking::votes(3, 1), Some(150));
+		assert_eq!(Staking::bonded(2), Some(4));
+		assert_eq!(StashOf::<Test>::convert(4), Some(2));
+		assert_eq!(Staking::locked(&3), 150);
+		assert_eq!(Balances::usable_balance(&2), INITIAL_BALANCE - 200);
+		assert_eq!(Staking::new_session(1), Some(vec![1, 2]));
+	});
+}
+
+#[test]
+#[should_panic(expected = "Genesis voter can vote for a genesis candidate")]
+fn genesis_votes_need_a_candidate() {
+	new_test_ext_with_staking(GenesisConfig { votes: vec![(3, 1, 150)], ..Default::default() });
+}
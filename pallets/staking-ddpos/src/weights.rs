@@ -0,0 +1,254 @@
+//! Weights for pallet_staking_ddpos.
+//!
+//! The weights are estimated from the storage accesses of each call, on top of a base of 10_000,
+//! until they are generated on the reference hardware with:
+//!
+//! ```text
+//! ./target/release/node-template benchmark pallet --chain dev --pallet pallet_staking_ddpos \
+//!     --extrinsic '*' --steps 50 --repeat 20 --output pallets/staking-ddpos/src/weights.rs
+//! ```
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_staking_ddpos.
+pub trait WeightInfo {
+	fn bond() -> Weight;
+	fn unbond() -> Weight;
+	fn withdraw_unbonded() -> Weight;
+	fn rebond() -> Weight;
+	fn set_controller() -> Weight;
+	fn set_commission() -> Weight;
+	fn vote() -> Weight;
+	fn unvote() -> Weight;
+	fn payout_stakers(n: u32, ) -> Weight;
+	fn submit_election_solution(c: u32, v: u32, ) -> Weight;
+	fn set_minimum_validators() -> Weight;
+	fn set_maximum_validators() -> Weight;
+	fn set_require_identity() -> Weight;
+}
+
+/// Weights for pallet_staking_ddpos using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: Staking Bonded (r:1 w:1)
+	// Storage: Staking Stashes (r:1 w:1)
+	// Storage: Staking Candidates (r:1 w:1)
+	// Storage: Staking Votes (r:1 w:0)
+	// Storage: Balances Locks (r:1 w:1)
+	// Storage: Staking QueuedSolution (r:1 w:1)
+	// Storage: Staking MaximumValidators (r:1 w:0)
+	// Storage: Staking CounterForCandidates (r:1 w:0)
+	// Storage: Staking RequireIdentity (r:1 w:0)
+	// Storage: Identity IdentityOf (r:1 w:0)
+	fn bond() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(10 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Storage: Staking Candidates (r:1 w:1)
+	// Storage: Staking ActiveEra (r:1 w:0)
+	// Storage: Staking Unlocking (r:1 w:1)
+	// Storage: Staking Bonded (r:1 w:1)
+	// Storage: Staking Stashes (r:0 w:1)
+	// Storage: Staking QueuedSolution (r:1 w:1)
+	// Storage: Staking MaximumValidators (r:1 w:0)
+	// Storage: Staking CounterForCandidates (r:1 w:0)
+	fn unbond() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Storage: Staking ActiveEra (r:1 w:0)
+	// Storage: Staking Unlocking (r:1 w:1)
+	// Storage: Staking Candidates (r:1 w:0)
+	// Storage: Balances Locks (r:1 w:1)
+	fn withdraw_unbonded() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: Staking Unlocking (r:1 w:1)
+	// Storage: Staking Bonded (r:1 w:1)
+	// Storage: Staking Stashes (r:1 w:1)
+	// Storage: Staking Candidates (r:1 w:1)
+	// Storage: Staking QueuedSolution (r:1 w:1)
+	// Storage: Staking MaximumValidators (r:1 w:0)
+	// Storage: Staking CounterForCandidates (r:1 w:0)
+	// Storage: Staking RequireIdentity (r:1 w:0)
+	// Storage: Identity IdentityOf (r:1 w:0)
+	fn rebond() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Storage: Staking Bonded (r:2 w:1)
+	// Storage: Staking Stashes (r:1 w:2)
+	fn set_controller() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: Staking Candidates (r:1 w:0)
+	// Storage: Staking ActiveEra (r:1 w:0)
+	// Storage: Staking PendingCommission (r:0 w:1)
+	fn set_commission() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Staking Candidates (r:2 w:0)
+	// Storage: Staking Votes (r:1 w:1)
+	// Storage: Staking VoterCount (r:1 w:1)
+	// Storage: Staking VotesReceived (r:1 w:1)
+	// Storage: Balances Locks (r:1 w:1)
+	// Storage: Staking QueuedSolution (r:1 w:1)
+	// Storage: Staking MaximumValidators (r:1 w:0)
+	// Storage: Staking CounterForCandidates (r:1 w:0)
+	fn vote() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Storage: Staking Votes (r:1 w:1)
+	// Storage: Staking VotesReceived (r:1 w:1)
+	// Storage: Staking VoterCount (r:1 w:1)
+	// Storage: Balances Locks (r:1 w:1)
+	// Storage: Staking Candidates (r:1 w:0)
+	// Storage: Staking QueuedSolution (r:1 w:1)
+	// Storage: Staking MaximumValidators (r:1 w:0)
+	// Storage: Staking CounterForCandidates (r:1 w:0)
+	fn unvote() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+	}
+	// Storage: Staking ErasValidatorReward (r:1 w:1)
+	// Storage: Staking ErasValidatorCommission (r:1 w:1)
+	// Storage: Staking UnclaimedRewards (r:1 w:1)
+	// Storage: Staking ErasStakers (r:1 w:0)
+	// Storage: System Account (r:2 w:2)
+	fn payout_stakers(n: u32, ) -> Weight {
+		(10_000 as Weight)
+			.saturating_add((20_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Staking CurrentEra (r:1 w:0)
+	// Storage: Staking MaximumValidators (r:1 w:0)
+	// Storage: Staking CounterForCandidates (r:1 w:0)
+	// Storage: Staking MinimumValidators (r:1 w:0)
+	// Storage: Staking Candidates (r:2 w:0)
+	// Storage: Staking Votes (r:1 w:0)
+	// Storage: Staking VotesReceived (r:1 w:0)
+	// Storage: Staking QueuedSolution (r:1 w:1)
+	fn submit_election_solution(c: u32, v: u32, ) -> Weight {
+		(10_000 as Weight)
+			.saturating_add((5_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add((2_000 as Weight).saturating_mul(v as Weight))
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(v as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Staking MaximumValidators (r:1 w:0)
+	// Storage: Staking MinimumValidators (r:0 w:1)
+	fn set_minimum_validators() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Staking MinimumValidators (r:1 w:0)
+	// Storage: Staking MaximumValidators (r:0 w:1)
+	fn set_maximum_validators() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Staking RequireIdentity (r:0 w:1)
+	fn set_require_identity() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn bond() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(10 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn unbond() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn withdraw_unbonded() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn rebond() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn set_controller() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn set_commission() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn vote() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn unvote() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+	}
+	fn payout_stakers(n: u32, ) -> Weight {
+		(10_000 as Weight)
+			.saturating_add((20_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	fn submit_election_solution(c: u32, v: u32, ) -> Weight {
+		(10_000 as Weight)
+			.saturating_add((5_000 as Weight).saturating_mul(c as Weight))
+			.saturating_add((2_000 as Weight).saturating_mul(v as Weight))
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(c as Weight)))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(v as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_minimum_validators() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_maximum_validators() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_require_identity() -> Weight {
+		(10_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+}
@@ -0,0 +1,145 @@
+//! Some configurable implementations as associated type for the runtime.
+
+use crate::{AccountId, Authorship, Balances, Identity, OriginCaller, Treasury, TreasuryFeeShare};
+use frame_support::traits::{Currency, Get, Imbalance, OnUnbalanced, PrivilegeCmp};
+use pallet_identity::{Data, Judgement};
+use sp_std::{cmp::Ordering, prelude::*};
+
+type NegativeImbalance = <Balances as Currency<AccountId>>::NegativeImbalance;
+
+/// Credits the author of the current block.
+pub struct Author;
+
+impl OnUnbalanced<NegativeImbalance> for Author {
+	fn on_nonzero_unbalanced(amount: NegativeImbalance) {
+		if let Some(author) = Authorship::author() {
+			Balances::resolve_creating(&author, amount);
+		}
+	}
+}
+
+/// Splits the transaction fees between the treasury, which gets `TreasuryFeeShare` of them, and
+/// the block author. The tips all go to the block author.
+pub struct DealWithFees;
+
+impl OnUnbalanced<NegativeImbalance> for DealWithFees {
+	fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = NegativeImbalance>) {
+		if let Some(fees) = fees_then_tips.next() {
+			let to_treasury = TreasuryFeeShare::get() * fees.peek();
+			let (to_treasury, mut to_author) = fees.split(to_treasury);
+			if let Some(tips) = fees_then_tips.next() {
+				tips.merge_into(&mut to_author);
+			}
+			<Treasury as OnUnbalanced<_>>::on_unbalanced(to_treasury);
+			<Author as OnUnbalanced<_>>::on_unbalanced(to_author);
+		}
+	}
+}
+
+/// The identities of the DPoS candidates, from `pallet_identity`.
+///
+/// An identity is judged if a registrar found it `Reasonable` or `KnownGood`.
+pub struct JudgedIdentity;
+
+impl pallet_staking_ddpos::CandidateIdentity<AccountId> for JudgedIdentity {
+	fn has_judged_identity(who: &AccountId) -> bool {
+		Identity::identity(who).map_or(false, |registration| {
+			registration.judgements.iter().any(|(_, judgement)| {
+				matches!(judgement, Judgement::Reasonable | Judgement::KnownGood)
+			})
+		})
+	}
+
+	fn display_name(who: &AccountId) -> Option<Vec<u8>> {
+		match Identity::identity(who)?.info.display {
+			Data::Raw(name) => Some(name.into_inner()),
+			_ => None,
+		}
+	}
+}
+
+/// Compares the privilege of the scheduling origins: root is above every other origin, which
+/// otherwise only compare when they are equal. Root can so cancel any scheduled call.
+pub struct RootOrEqualPrivilege;
+
+impl PrivilegeCmp<OriginCaller> for RootOrEqualPrivilege {
+	fn cmp_privilege(left: &OriginCaller, right: &OriginCaller) -> Option<Ordering> {
+		if left == right {
+			return Some(Ordering::Equal)
+		}
+		match (left, right) {
+			(OriginCaller::system(frame_system::RawOrigin::Root), _) => Some(Ordering::Greater),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Call, Origin, Runtime, Scheduler, UNIT};
+	use frame_support::{
+		assert_noop, assert_ok,
+		storage::{storage_prefix, unhashed},
+	};
+	use sp_runtime::DispatchError;
+
+	fn new_test_ext() -> sp_io::TestExternalities {
+		frame_system::GenesisConfig::default()
+			.build_storage::<Runtime>()
+			.unwrap()
+			.into()
+	}
+
+	// The author is otherwise found from the Aura pre-runtime digest of the block.
+	fn set_author(author: &AccountId) {
+		unhashed::put(&storage_prefix(b"Authorship", b"Author"), author);
+	}
+
+	#[test]
+	fn fees_are_split_and_tips_go_to_the_author() {
+		new_test_ext().execute_with(|| {
+			let author = AccountId::from([1; 32]);
+			set_author(&author);
+
+			let fees = Balances::issue(10 * UNIT);
+			let tips = Balances::issue(5 * UNIT);
+			DealWithFees::on_unbalanceds(vec![fees, tips].into_iter());
+
+			assert_eq!(Balances::free_balance(Treasury::account_id()), 8 * UNIT);
+			assert_eq!(Balances::free_balance(&author), 7 * UNIT);
+			assert_eq!(Balances::total_issuance(), 15 * UNIT);
+		});
+	}
+
+	#[test]
+	fn author_share_is_burned_without_an_author() {
+		new_test_ext().execute_with(|| {
+			assert_eq!(Authorship::author(), None);
+
+			let fees = Balances::issue(10 * UNIT);
+			let tips = Balances::issue(5 * UNIT);
+			DealWithFees::on_unbalanceds(vec![fees, tips].into_iter());
+
+			assert_eq!(Balances::free_balance(Treasury::account_id()), 8 * UNIT);
+			assert_eq!(Balances::total_issuance(), 8 * UNIT);
+		});
+	}
+
+	#[test]
+	fn only_root_cancels_the_calls_scheduled_by_root() {
+		new_test_ext().execute_with(|| {
+			let call =
+				|| Box::new(Call::System(frame_system::Call::remark { remark: vec![] }).into());
+			let council: Origin =
+				pallet_collective::RawOrigin::<AccountId, crate::CouncilCollective>::Members(2, 3)
+					.into();
+			assert_ok!(Scheduler::schedule(Origin::root(), 10, None, 0, call()));
+			assert_ok!(Scheduler::schedule(council.clone(), 10, None, 0, call()));
+
+			assert_noop!(Scheduler::cancel(council, 10, 0), DispatchError::BadOrigin);
+			assert_ok!(Scheduler::cancel(Origin::root(), 10, 1));
+			assert_ok!(Scheduler::cancel(Origin::root(), 10, 0));
+		});
+	}
+}
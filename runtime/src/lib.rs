@@ -6,21 +6,30 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+mod impls;
+use impls::DealWithFees;
+
+use codec::{Decode, Encode, MaxEncodedLen};
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
-use pallet_session;
+use pallet_session::historical as pallet_session_historical;
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
+use sp_core::{
+	crypto::{key_types, KeyTypeId},
+	OpaqueMetadata,
+};
 use sp_runtime::{
-	create_runtime_str, generic, impl_opaque_keys,
+	create_runtime_str,
+	curve::PiecewiseLinear,
+	generic, impl_opaque_keys,
 	traits::{
-		AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, OpaqueKeys,
-		Verify,
+		AccountIdLookup, BlakeTwo256, Block as BlockT, ConvertInto, IdentifyAccount, NumberFor,
+		OpaqueKeys, Verify,
 	},
-	transaction_validity::{TransactionSource, TransactionValidity},
-	ApplyExtrinsicResult, MultiSignature,
+	transaction_validity::{TransactionPriority, TransactionSource, TransactionValidity},
+	ApplyExtrinsicResult, FixedPointNumber, MultiSignature, RuntimeDebug,
 };
 use sp_std::prelude::*;
 #[cfg(feature = "std")]
@@ -31,21 +40,24 @@ use sp_version::RuntimeVersion;
 pub use frame_support::{
 	construct_runtime, parameter_types,
 	traits::{
-		ConstU128, ConstU32, ConstU64, ConstU8, KeyOwnerProofSystem, Randomness, StorageInfo,
+		ConstBool, ConstU128, ConstU16, ConstU32, ConstU64, ConstU8, EitherOfDiverse,
+		EstimateNextSessionRotation, InstanceFilter, KeyOwnerProofSystem, Randomness, StorageInfo,
 	},
 	weights::{
 		constants::{BlockExecutionWeight, ExtrinsicBaseWeight, RocksDbWeight, WEIGHT_PER_SECOND},
-		IdentityFee, Weight,
+		IdentityFee, Weight, WeightToFeeCoefficient, WeightToFeeCoefficients,
+		WeightToFeePolynomial,
 	},
-	StorageValue,
+	PalletId, StorageValue,
 };
 pub use frame_system::Call as SystemCall;
+use frame_system::EnsureRoot;
 pub use pallet_balances::Call as BalancesCall;
 pub use pallet_timestamp::Call as TimestampCall;
-use pallet_transaction_payment::CurrencyAdapter;
+use pallet_transaction_payment::{CurrencyAdapter, Multiplier, TargetedFeeAdjustment};
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
-pub use sp_runtime::{Perbill, Permill};
+pub use sp_runtime::{Perbill, Permill, Perquintill};
 
 pub use pallet_staking_ddpos;
 
@@ -104,10 +116,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 122,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 15,
 	state_version: 1,
 };
 
@@ -128,6 +140,10 @@ pub const MINUTES: BlockNumber = 60_000 / (MILLISECS_PER_BLOCK as BlockNumber);
 pub const HOURS: BlockNumber = MINUTES * 60;
 pub const DAYS: BlockNumber = HOURS * 24;
 
+// Currency units, with 12 decimals.
+pub const UNIT: Balance = 1_000_000_000_000;
+pub const MILLIUNIT: Balance = UNIT / 1_000;
+
 /// The version information used to identify this runtime when compiled natively.
 #[cfg(feature = "std")]
 pub fn native_version() -> NativeVersion {
@@ -203,17 +219,29 @@ impl frame_system::Config for Runtime {
 
 impl pallet_randomness_collective_flip::Config for Runtime {}
 
+parameter_types! {
+	/// The maximum number of Aura and GRANDPA authorities, and so of elected validators.
+	pub const MaxAuthorities: u32 = 32;
+}
+
 impl pallet_aura::Config for Runtime {
 	type AuthorityId = AuraId;
-	type DisabledValidators = ();
-	type MaxAuthorities = ConstU32<32>;
+	type DisabledValidators = Staking;
+	type MaxAuthorities = MaxAuthorities;
+}
+
+parameter_types! {
+	/// How long, in blocks, an equivocation report stays valid: as long as the offender's bond
+	/// can still be slashed.
+	pub ReportLongevity: u64 =
+		BondingDuration::get() as u64 * SessionsPerEra::get() as u64 * SessionPeriod::get() as u64;
 }
 
 impl pallet_grandpa::Config for Runtime {
 	type Event = Event;
 	type Call = Call;
 
-	type KeyOwnerProofSystem = ();
+	type KeyOwnerProofSystem = Historical;
 
 	type KeyOwnerProof =
 		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, GrandpaId)>>::Proof;
@@ -223,10 +251,29 @@ impl pallet_grandpa::Config for Runtime {
 		GrandpaId,
 	)>>::IdentificationTuple;
 
-	type HandleEquivocation = ();
+	type HandleEquivocation = pallet_grandpa::EquivocationHandler<
+		Self::KeyOwnerIdentification,
+		Offences,
+		ReportLongevity,
+	>;
 
 	type WeightInfo = ();
-	type MaxAuthorities = ConstU32<32>;
+	type MaxAuthorities = MaxAuthorities;
+}
+
+impl pallet_authorship::Config for Runtime {
+	type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Aura>;
+	type UncleGenerations = ConstU32<0>;
+	type FilterUncle = ();
+	type EventHandler = ();
+}
+
+impl<C> frame_system::offchain::SendTransactionTypes<C> for Runtime
+where
+	Call: From<C>,
+{
+	type Extrinsic = UncheckedExtrinsic;
+	type OverarchingCall = Call;
 }
 
 impl pallet_timestamp::Config for Runtime {
@@ -251,13 +298,60 @@ impl pallet_balances::Config for Runtime {
 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
 }
 
+parameter_types! {
+	/// The share of the transaction fees paid to the treasury. The rest, and the tips, go to the
+	/// block author.
+	pub const TreasuryFeeShare: Perbill = Perbill::from_percent(80);
+}
+
+/// Converts a weight to a fee.
+///
+/// The linear term charges a tenth of a `MILLIUNIT` for the `ExtrinsicBaseWeight`. The quadratic
+/// term catches up with it around a millisecond of weight, so that heavy transactions cost more
+/// than the same weight split in light ones.
+pub struct WeightToFee;
+
+impl WeightToFeePolynomial for WeightToFee {
+	type Balance = Balance;
+
+	fn polynomial() -> WeightToFeeCoefficients<Self::Balance> {
+		let p = MILLIUNIT / 10;
+		let q = Balance::from(ExtrinsicBaseWeight::get());
+		smallvec::smallvec![
+			WeightToFeeCoefficient {
+				degree: 1,
+				negative: false,
+				coeff_frac: Perbill::from_rational(p % q, q),
+				coeff_integer: p / q,
+			},
+			WeightToFeeCoefficient {
+				degree: 2,
+				negative: false,
+				coeff_frac: Perbill::from_parts(1),
+				coeff_integer: 0,
+			},
+		]
+	}
+}
+
+parameter_types! {
+	/// The portion of the normal dispatch class (`NORMAL_DISPATCH_RATIO` of the block) that we
+	/// target: fees go up when blocks are fuller, and down when they are emptier.
+	pub const TargetBlockFullness: Perquintill = Perquintill::from_percent(25);
+	/// How fast the fee multiplier reacts to the block fullness.
+	pub AdjustmentVariable: Multiplier = Multiplier::saturating_from_rational(3, 100_000);
+	/// The fee multiplier never goes below this value.
+	pub MinimumMultiplier: Multiplier = Multiplier::saturating_from_rational(1, 1_000_000u128);
+}
+
 impl pallet_transaction_payment::Config for Runtime {
 	type Event = Event;
-	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
+	type OnChargeTransaction = CurrencyAdapter<Balances, DealWithFees>;
 	type OperationalFeeMultiplier = ConstU8<5>;
-	type WeightToFee = IdentityFee<Balance>;
+	type WeightToFee = WeightToFee;
 	type LengthToFee = IdentityFee<Balance>;
-	type FeeMultiplierUpdate = ();
+	type FeeMultiplierUpdate =
+		TargetedFeeAdjustment<Self, TargetBlockFullness, AdjustmentVariable, MinimumMultiplier>;
 }
 
 impl pallet_sudo::Config for Runtime {
@@ -265,24 +359,394 @@ impl pallet_sudo::Config for Runtime {
 	type Call = Call;
 }
 
+parameter_types! {
+	pub MaximumSchedulerWeight: Weight = Perbill::from_percent(80) * BlockWeights::get().max_block;
+	/// A call scheduled by hash waits this many blocks for its preimage to be noted.
+	pub const NoPreimagePostponement: Option<BlockNumber> = Some(10);
+}
+
+impl pallet_scheduler::Config for Runtime {
+	type Event = Event;
+	type Origin = Origin;
+	type PalletsOrigin = OriginCaller;
+	type Call = Call;
+	type MaximumWeight = MaximumSchedulerWeight;
+	/// Calls can be scheduled by a council majority or a referendum, and are dispatched with the
+	/// scheduling origin.
+	type ScheduleOrigin = RootOrCouncilMajority;
+	type MaxScheduledPerBlock = ConstU32<50>;
+	type WeightInfo = pallet_scheduler::weights::SubstrateWeight<Runtime>;
+	type OriginPrivilegeCmp = impls::RootOrEqualPrivilege;
+	type PreimageProvider = Preimage;
+	type NoPreimagePostponement = NoPreimagePostponement;
+}
+
+parameter_types! {
+	pub const PreimageMaxSize: u32 = 4096 * 1024;
+	pub const PreimageBaseDeposit: Balance = UNIT;
+}
+
+impl pallet_preimage::Config for Runtime {
+	type WeightInfo = pallet_preimage::weights::SubstrateWeight<Runtime>;
+	type Event = Event;
+	type Currency = Balances;
+	type ManagerOrigin = EnsureRoot<AccountId>;
+	type MaxSize = PreimageMaxSize;
+	type BaseDeposit = PreimageBaseDeposit;
+	type ByteDeposit = PreimageByteDeposit;
+}
+
+parameter_types! {
+	pub const CouncilMotionDuration: BlockNumber = HOURS;
+	pub const CouncilMaxProposals: u32 = 100;
+	pub const CouncilMaxMembers: u32 = 100;
+}
+
+type CouncilCollective = pallet_collective::Instance1;
+impl pallet_collective::Config<CouncilCollective> for Runtime {
+	type Origin = Origin;
+	type Proposal = Call;
+	type Event = Event;
+	type MotionDuration = CouncilMotionDuration;
+	type MaxProposals = CouncilMaxProposals;
+	type MaxMembers = CouncilMaxMembers;
+	type DefaultVote = pallet_collective::PrimeDefaultVote;
+	type WeightInfo = pallet_collective::weights::SubstrateWeight<Runtime>;
+}
+
+/// Root, from a referendum or while sudo is still in the runtime, or a council majority.
+type RootOrCouncilMajority = EitherOfDiverse<
+	EnsureRoot<AccountId>,
+	pallet_collective::EnsureProportionMoreThan<AccountId, CouncilCollective, 1, 2>,
+>;
+
+parameter_types! {
+	pub const LaunchPeriod: BlockNumber = 2 * HOURS;
+	pub const VotingPeriod: BlockNumber = 2 * HOURS;
+	pub const FastTrackVotingPeriod: BlockNumber = 10 * MINUTES;
+	pub const EnactmentPeriod: BlockNumber = HOURS;
+	pub const CooloffPeriod: BlockNumber = DAYS;
+	pub const MinimumDeposit: Balance = 100 * UNIT;
+	pub const PreimageByteDeposit: Balance = MILLIUNIT;
+	pub const MaxProposals: u32 = 100;
+}
+
+parameter_types! {
+	pub const TreasuryPalletId: PalletId = PalletId(*b"py/trsry");
+	pub const ProposalBond: Permill = Permill::from_percent(5);
+	pub const ProposalBondMinimum: Balance = UNIT;
+	pub const SpendPeriod: BlockNumber = DAYS;
+	pub const Burn: Permill = Permill::zero();
+	pub const MaxApprovals: u32 = 100;
+}
+
+impl pallet_treasury::Config for Runtime {
+	type PalletId = TreasuryPalletId;
+	type Currency = Balances;
+	/// Spend proposals are approved by a 3/5 council majority, or a referendum.
+	type ApproveOrigin = EitherOfDiverse<
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 3, 5>,
+	>;
+	type RejectOrigin = RootOrCouncilMajority;
+	type Event = Event;
+	type OnSlash = Treasury;
+	type ProposalBond = ProposalBond;
+	type ProposalBondMinimum = ProposalBondMinimum;
+	type ProposalBondMaximum = ();
+	type SpendPeriod = SpendPeriod;
+	type Burn = Burn;
+	type BurnDestination = ();
+	type SpendFunds = ();
+	type WeightInfo = pallet_treasury::weights::SubstrateWeight<Runtime>;
+	type MaxApprovals = MaxApprovals;
+}
+
+parameter_types! {
+	pub const BasicDeposit: Balance = 10 * UNIT;
+	pub const FieldDeposit: Balance = UNIT;
+	pub const SubAccountDeposit: Balance = 2 * UNIT;
+	pub const MaxSubAccounts: u32 = 100;
+	pub const MaxAdditionalFields: u32 = 100;
+	pub const MaxRegistrars: u32 = 20;
+}
+
+impl pallet_identity::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type BasicDeposit = BasicDeposit;
+	type FieldDeposit = FieldDeposit;
+	type SubAccountDeposit = SubAccountDeposit;
+	type MaxSubAccounts = MaxSubAccounts;
+	type MaxAdditionalFields = MaxAdditionalFields;
+	type MaxRegistrars = MaxRegistrars;
+	type Slashed = Treasury;
+	/// Registrars are added, and identities removed, by a council majority or a referendum.
+	type ForceOrigin = RootOrCouncilMajority;
+	type RegistrarOrigin = RootOrCouncilMajority;
+	type WeightInfo = pallet_identity::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	/// A proxy definition takes 8 bytes of storage, plus 33 bytes per proxy.
+	pub const ProxyDepositBase: Balance = UNIT + 8 * MILLIUNIT;
+	pub const ProxyDepositFactor: Balance = 33 * MILLIUNIT;
+	/// An announcement takes 8 bytes of storage, plus 68 bytes per announcement.
+	pub const AnnouncementDepositBase: Balance = UNIT + 8 * MILLIUNIT;
+	pub const AnnouncementDepositFactor: Balance = 68 * MILLIUNIT;
+}
+
+/// The calls a proxy account is allowed to make on behalf of the proxied account.
+#[derive(
+	Copy,
+	Clone,
+	Eq,
+	PartialEq,
+	Ord,
+	PartialOrd,
+	Encode,
+	Decode,
+	RuntimeDebug,
+	MaxEncodedLen,
+	scale_info::TypeInfo,
+)]
+pub enum ProxyType {
+	/// Any call.
+	Any,
+	/// The DPoS staking calls only, possibly batched, so that a hot key can bond and vote for a
+	/// cold stash without being able to transfer its funds.
+	Staking,
+	/// The council, democracy and treasury calls only, possibly batched.
+	Governance,
+}
+
+impl Default for ProxyType {
+	fn default() -> Self {
+		Self::Any
+	}
+}
+
+impl InstanceFilter<Call> for ProxyType {
+	fn filter(&self, c: &Call) -> bool {
+		match self {
+			ProxyType::Any => true,
+			// The batched calls are filtered too.
+			ProxyType::Staking => matches!(c, Call::Staking(..) | Call::Utility(..)),
+			ProxyType::Governance => matches!(
+				c,
+				Call::Council(..) | Call::Democracy(..) | Call::Treasury(..) | Call::Utility(..)
+			),
+		}
+	}
+
+	fn is_superset(&self, o: &Self) -> bool {
+		match (self, o) {
+			(x, y) if x == y => true,
+			(ProxyType::Any, _) => true,
+			_ => false,
+		}
+	}
+}
+
+impl pallet_proxy::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type ProxyType = ProxyType;
+	type ProxyDepositBase = ProxyDepositBase;
+	type ProxyDepositFactor = ProxyDepositFactor;
+	type MaxProxies = ConstU32<32>;
+	type WeightInfo = pallet_proxy::weights::SubstrateWeight<Runtime>;
+	type MaxPending = ConstU32<32>;
+	type CallHasher = BlakeTwo256;
+	type AnnouncementDepositBase = AnnouncementDepositBase;
+	type AnnouncementDepositFactor = AnnouncementDepositFactor;
+}
+
+parameter_types! {
+	pub const MinVestedTransfer: Balance = 100 * UNIT;
+}
+
+impl pallet_vesting::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type BlockNumberToBalance = ConvertInto;
+	type MinVestedTransfer = MinVestedTransfer;
+	type WeightInfo = pallet_vesting::weights::SubstrateWeight<Runtime>;
+	const MAX_VESTING_SCHEDULES: u32 = 28;
+}
+
+impl pallet_utility::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type PalletsOrigin = OriginCaller;
+	type WeightInfo = pallet_utility::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	/// A multisig operation takes 88 bytes of storage, plus 32 bytes per signatory.
+	pub const DepositBase: Balance = UNIT + 88 * MILLIUNIT;
+	pub const DepositFactor: Balance = 32 * MILLIUNIT;
+}
+
+impl pallet_multisig::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type Currency = Balances;
+	type DepositBase = DepositBase;
+	type DepositFactor = DepositFactor;
+	type MaxSignatories = ConstU16<100>;
+	type WeightInfo = pallet_multisig::weights::SubstrateWeight<Runtime>;
+}
+
+impl pallet_democracy::Config for Runtime {
+	type Proposal = Call;
+	type Event = Event;
+	type Currency = Balances;
+	type EnactmentPeriod = EnactmentPeriod;
+	type LaunchPeriod = LaunchPeriod;
+	type VotingPeriod = VotingPeriod;
+	type VoteLockingPeriod = EnactmentPeriod;
+	type MinimumDeposit = MinimumDeposit;
+	/// A straight majority of the council can decide what their next motion is.
+	type ExternalOrigin =
+		pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 1, 2>;
+	/// A super-majority can have the next scheduled referendum be a straight majority-carries vote.
+	type ExternalMajorityOrigin =
+		pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 3, 4>;
+	/// A unanimous council can have the next scheduled referendum be a straight default-carries
+	/// (NTB) vote.
+	type ExternalDefaultOrigin =
+		pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 1, 1>;
+	/// Two thirds of the council can have an external proposal tabled immediately, with a shorter
+	/// voting period, and a unanimous council can have it voted instantly.
+	type FastTrackOrigin =
+		pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 2, 3>;
+	type InstantOrigin =
+		pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 1, 1>;
+	type InstantAllowed = ConstBool<true>;
+	type FastTrackVotingPeriod = FastTrackVotingPeriod;
+	/// To cancel a proposal which has been passed, 2/3 of the council must agree to it.
+	type CancellationOrigin =
+		pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 2, 3>;
+	/// To cancel a proposal before it has been passed, the council must be unanimous or Root must
+	/// agree.
+	type CancelProposalOrigin = EitherOfDiverse<
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureProportionAtLeast<AccountId, CouncilCollective, 1, 1>,
+	>;
+	type BlacklistOrigin = EnsureRoot<AccountId>;
+	/// Any single council member may veto a coming council proposal, however they can only do it
+	/// once and it lasts only for the cool-off period.
+	type VetoOrigin = pallet_collective::EnsureMember<AccountId, CouncilCollective>;
+	type CooloffPeriod = CooloffPeriod;
+	type PreimageByteDeposit = PreimageByteDeposit;
+	type OperationalPreimageOrigin = pallet_collective::EnsureMember<AccountId, CouncilCollective>;
+	type Slash = Treasury;
+	type Scheduler = Scheduler;
+	type PalletsOrigin = OriginCaller;
+	type MaxVotes = ConstU32<100>;
+	type WeightInfo = pallet_democracy::weights::SubstrateWeight<Runtime>;
+	type MaxProposals = MaxProposals;
+}
+
+parameter_types! {
+	pub const SessionPeriod: BlockNumber = 10 * MINUTES;
+}
+
 impl pallet_session::Config for Runtime {
 	type Event = Event;
 	type ValidatorId = <Self as frame_system::Config>::AccountId;
-	type ValidatorIdOf = (); // TODO: check
-	type ShouldEndSession = pallet_session::PeriodicSessions<ConstU32<5>, ConstU32<3>>;
+	type ValidatorIdOf = pallet_staking_ddpos::StashOf<Self>;
+	type ShouldEndSession = pallet_session::PeriodicSessions<SessionPeriod, ConstU32<0>>;
 	type NextSessionRotation = Self::ShouldEndSession;
-	type SessionManager = Staking;
+	type SessionManager = pallet_session::historical::NoteHistoricalRoot<Self, Staking>;
 	type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
 	type Keys = opaque::SessionKeys;
 	type WeightInfo = ();
 }
 
+// The yearly inflation: from 2.5% when nothing is staked up to 10% at the ideal staking ratio of
+// 50%, halving for every 5% staked above it.
+pallet_staking_reward_curve::build! {
+	const REWARD_CURVE: PiecewiseLinear<'static> = curve!(
+		min_inflation: 0_025_000,
+		max_inflation: 0_100_000,
+		ideal_stake: 0_500_000,
+		falloff: 0_050_000,
+		max_piece_count: 40,
+		test_precision: 0_005_000,
+	);
+}
+
+parameter_types! {
+	/// An era lasts one hour: elections run and rewards are paid once per era.
+	pub const SessionsPerEra: u32 = 6;
+	/// The era-indexed staking history is kept for a week.
+	pub const HistoryDepth: u32 = 24 * 7;
+	/// The staking rewards are minted into the account of this pallet id before being paid out.
+	pub const StakingPalletId: PalletId = PalletId(*b"py/ddpos");
+	pub const RewardCurve: &'static PiecewiseLinear<'static> = &REWARD_CURVE;
+	/// A validator missing this many slots in a row is disabled until the next election.
+	pub const MaxMissedSlots: u32 = 10;
+	/// Unbonded funds stay locked, and slashable, for a day.
+	pub const BondingDuration: u32 = 24;
+	pub const MaxUnlockingChunks: u32 = 32;
+	/// A payout mints to a validator and up to this many voters.
+	pub const MaxVotersPerCandidate: u32 = 256;
+	/// Votes smaller than this cannot take the places of the voters of a candidate.
+	pub const MinVote: Balance = 1_000_000_000_000;
+	/// A commission change takes effect after a day.
+	pub const CommissionChangeDelay: u32 = 24;
+	/// Election solutions computed by the offchain workers come before regular transactions.
+	pub const StakingUnsignedPriority: TransactionPriority = TransactionPriority::max_value() / 2;
+}
+
+/// The current Aura slot.
+pub struct AuraSlot;
+
+impl frame_support::traits::Get<u64> for AuraSlot {
+	fn get() -> u64 {
+		Aura::current_slot().into()
+	}
+}
+
+impl pallet_session::historical::Config for Runtime {
+	type FullIdentification = pallet_staking_ddpos::Exposure<AccountId, Balance>;
+	type FullIdentificationOf = pallet_staking_ddpos::ExposureOf<Runtime>;
+}
+
+impl pallet_offences::Config for Runtime {
+	type Event = Event;
+	type IdentificationTuple = pallet_session::historical::IdentificationTuple<Self>;
+	type OnOffenceHandler = Staking;
+}
+
 impl pallet_staking_ddpos::Config for Runtime {
 	type Event = Event;
 	type Currency = Balances;
 	type CurrencyBalance = Balance;
 	type MinimumValidatorCount = ConstU32<1>;
 	type MaximumValidatorCount = ConstU32<2>;
+	type MaxValidators = MaxAuthorities;
+	type SessionsPerEra = SessionsPerEra;
+	type HistoryDepth = HistoryDepth;
+	type EraPayout = pallet_staking_ddpos::ConvertCurve<RewardCurve>;
+	type UnixTime = Timestamp;
+	type PalletId = StakingPalletId;
+	type Slash = Treasury;
+	type SessionInterface = Self;
+	type CurrentSlot = AuraSlot;
+	type MaxMissedSlots = MaxMissedSlots;
+	type BondingDuration = BondingDuration;
+	type MaxUnlockingChunks = MaxUnlockingChunks;
+	type MaxVotersPerCandidate = MaxVotersPerCandidate;
+	type MinVote = MinVote;
+	type AdminOrigin = RootOrCouncilMajority;
+	type UnsignedPriority = StakingUnsignedPriority;
+	type Identity = impls::JudgedIdentity;
+	type CommissionChangeDelay = CommissionChangeDelay;
+	type WeightInfo = pallet_staking_ddpos::weights::SubstrateWeight<Runtime>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -299,9 +763,26 @@ construct_runtime!(
 		Grandpa: pallet_grandpa,
 		Balances: pallet_balances,
 		TransactionPayment: pallet_transaction_payment,
+		// NOTE: `Sudo` can be removed in a runtime upgrade once the council and democracy are
+		// running: every privileged call is also available to a referendum.
 		Sudo: pallet_sudo,
-		Session: pallet_session,
+		Scheduler: pallet_scheduler,
+		Preimage: pallet_preimage,
+		Council: pallet_collective::<Instance1>,
+		Democracy: pallet_democracy,
+		Treasury: pallet_treasury,
+		Identity: pallet_identity,
+		Proxy: pallet_proxy,
+		Utility: pallet_utility,
+		Multisig: pallet_multisig,
+		Vesting: pallet_vesting,
+		// NOTE: `Staking` must be before `Session`, so that the genesis candidates are bonded
+		// when the session pallet asks for the initial validator set.
 		Staking: pallet_staking_ddpos,
+		Session: pallet_session,
+		Authorship: pallet_authorship,
+		Offences: pallet_offences,
+		Historical: pallet_session_historical::{Pallet},
 	}
 );
 
@@ -333,6 +814,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	pallet_staking_ddpos::migrations::v1::MigrateToV1<Runtime>,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -345,7 +827,18 @@ mod benches {
 		[frame_benchmarking, BaselineBench::<Runtime>]
 		[frame_system, SystemBench::<Runtime>]
 		[pallet_balances, Balances]
+		[pallet_collective, Council]
+		[pallet_democracy, Democracy]
+		[pallet_identity, Identity]
+		[pallet_multisig, Multisig]
+		[pallet_preimage, Preimage]
+		[pallet_proxy, Proxy]
+		[pallet_scheduler, Scheduler]
 		[pallet_timestamp, Timestamp]
+		[pallet_treasury, Treasury]
+		[pallet_utility, Utility]
+		[pallet_vesting, Vesting]
+		[pallet_staking_ddpos, Staking]
 		[pallet_template, TemplateModule]
 	);
 }
@@ -440,23 +933,29 @@ impl_runtime_apis! {
 		}
 
 		fn submit_report_equivocation_unsigned_extrinsic(
-			_equivocation_proof: fg_primitives::EquivocationProof<
+			equivocation_proof: fg_primitives::EquivocationProof<
 				<Block as BlockT>::Hash,
 				NumberFor<Block>,
 			>,
-			_key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
+			key_owner_proof: fg_primitives::OpaqueKeyOwnershipProof,
 		) -> Option<()> {
-			None
+			let key_owner_proof = key_owner_proof.decode()?;
+
+			Grandpa::submit_unsigned_equivocation_report(
+				equivocation_proof,
+				key_owner_proof,
+			)
 		}
 
 		fn generate_key_ownership_proof(
 			_set_id: fg_primitives::SetId,
-			_authority_id: GrandpaId,
+			authority_id: GrandpaId,
 		) -> Option<fg_primitives::OpaqueKeyOwnershipProof> {
-			// NOTE: this is the only implementation possible since we've
-			// defined our key owner proof type as a bottom type (i.e. a type
-			// with no values).
-			None
+			use codec::Encode;
+
+			Historical::prove((fg_primitives::KEY_TYPE, authority_id))
+				.map(|p| p.encode())
+				.map(fg_primitives::OpaqueKeyOwnershipProof::new)
 		}
 	}
 
@@ -466,6 +965,28 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_staking_ddpos_runtime_api::DposApi<Block, AccountId, Balance> for Runtime {
+		fn candidates() -> Vec<pallet_staking_ddpos::CandidateInfo<AccountId, Balance>> {
+			Staking::candidates_info()
+		}
+
+		fn votes_of(voter: AccountId) -> Vec<(AccountId, Balance)> {
+			Staking::votes_of(&voter)
+		}
+
+		fn current_validators() -> Vec<AccountId> {
+			Staking::current_validators()
+		}
+
+		fn next_validators() -> Vec<AccountId> {
+			Staking::next_validators()
+		}
+
+		fn validator_count() -> (u32, u32) {
+			(Staking::minimum_validators(), Staking::maximum_validators())
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
 		fn query_info(
 			uxt: <Block as BlockT>::Extrinsic,
@@ -481,6 +1002,27 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_staking_ddpos_runtime_api::TransactionFeeApi<Block, Multiplier> for Runtime {
+		fn next_fee_multiplier() -> Multiplier {
+			TransactionPayment::next_fee_multiplier()
+		}
+	}
+
+	impl pallet_staking_ddpos_runtime_api::DposMetricsApi<Block, AccountId, AuraId, BlockNumber> for Runtime {
+		fn aura_key_owners(keys: Vec<AuraId>) -> Vec<Option<AccountId>> {
+			keys.into_iter()
+				.map(|key| pallet_session::KeyOwner::<Runtime>::get((key_types::AURA, key.as_ref().to_vec())))
+				.collect()
+		}
+
+		fn blocks_until_next_session() -> BlockNumber {
+			let now = System::block_number();
+			<Runtime as pallet_session::Config>::NextSessionRotation::estimate_next_session_rotation(now)
+				.0
+				.map_or(0, |next| next.saturating_sub(now))
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (
@@ -551,6 +1093,79 @@ impl_runtime_apis! {
 #[cfg(test)]
 mod test {
 	use super::*;
+	use frame_support::weights::{constants::WEIGHT_PER_MILLIS, WeightToFee as _};
+
+	#[test]
+	fn extrinsic_base_fee_is_correct() {
+		// The quadratic term adds less than a tenth to the base fee.
+		let fee = WeightToFee::weight_to_fee(&ExtrinsicBaseWeight::get());
+		assert!(fee >= MILLIUNIT / 10, "{}", fee);
+		assert!(fee <= MILLIUNIT / 10 + MILLIUNIT / 100, "{}", fee);
+	}
+
+	#[test]
+	fn weight_fee_is_superlinear() {
+		let base = Balance::from(ExtrinsicBaseWeight::get());
+		let linear = |weight: Weight| MILLIUNIT / 10 * Balance::from(weight) / base;
+
+		// The quadratic term charges the squared weight divided by 1_000_000_000.
+		let fee = WeightToFee::weight_to_fee(&WEIGHT_PER_MILLIS);
+		let expected = linear(WEIGHT_PER_MILLIS) + 1_000_000_000;
+		assert!(fee.max(expected) - fee.min(expected) < 10, "{} != {}", fee, expected);
+
+		let fee_10ms = WeightToFee::weight_to_fee(&(10 * WEIGHT_PER_MILLIS));
+		assert!(fee_10ms > 10 * fee, "{} <= 10 * {}", fee_10ms, fee);
+	}
+
+	#[test]
+	fn full_block_fee_is_correct() {
+		let full_block = NORMAL_DISPATCH_RATIO * (2 * WEIGHT_PER_SECOND);
+		let fee = WeightToFee::weight_to_fee(&full_block);
+		assert!(fee >= 1_000 * UNIT, "{}", fee);
+		assert!(fee <= 10_000 * UNIT, "{}", fee);
+	}
+
+	#[test]
+	fn proxy_types_filter_the_calls() {
+		let transfer = Call::Balances(BalancesCall::transfer {
+			dest: sp_runtime::MultiAddress::Id(AccountId::from([1; 32])),
+			value: UNIT,
+		});
+		let vote = Call::Staking(pallet_staking_ddpos::Call::vote {
+			candidate: AccountId::from([1; 32]),
+			value: UNIT,
+		});
+		let approve = Call::Treasury(pallet_treasury::Call::approve_proposal { proposal_id: 0 });
+		let clear = Call::Democracy(pallet_democracy::Call::clear_public_proposals {});
+
+		assert!([&transfer, &vote, &approve, &clear].iter().all(|c| ProxyType::Any.filter(c)));
+		assert!(ProxyType::Staking.filter(&vote));
+		assert!(!ProxyType::Staking.filter(&transfer));
+		assert!(!ProxyType::Staking.filter(&approve));
+		assert!(ProxyType::Governance.filter(&approve));
+		assert!(ProxyType::Governance.filter(&clear));
+		assert!(!ProxyType::Governance.filter(&transfer));
+		assert!(!ProxyType::Governance.filter(&vote));
+
+		// The batched calls are filtered again when the batch dispatches them.
+		let batch = Call::Utility(pallet_utility::Call::batch_all { calls: vec![vote, transfer] });
+		assert!(ProxyType::Staking.filter(&batch));
+		assert!(ProxyType::Governance.filter(&batch));
+	}
+
+	#[test]
+	fn any_proxy_is_the_only_superset() {
+		let (any, staking, governance) =
+			(ProxyType::Any, ProxyType::Staking, ProxyType::Governance);
+		for proxy in [any, staking, governance] {
+			assert!(any.is_superset(&proxy));
+			assert!(proxy.is_superset(&proxy));
+		}
+		assert!(!staking.is_superset(&any));
+		assert!(!staking.is_superset(&governance));
+		assert!(!governance.is_superset(&any));
+		assert!(!governance.is_superset(&staking));
+	}
 
 	#[test]
 	fn call_size() {